@@ -0,0 +1,40 @@
+use eframe::egui;
+
+use crate::engine::EquityValue;
+
+use super::{float_field, signed_float_field, AppState};
+
+impl AppState {
+    /// Equity bridge section of the left panel.
+    pub(super) fn bridge_ui(&mut self, ui: &mut egui::Ui) {
+        let width = ui.available_width();
+        let bridge = &mut self.state.bridge;
+        // net cash is negative net debt
+        let mut changed = signed_float_field(ui, width, "Net Debt: ", &mut bridge.net_debt);
+        changed |= float_field(ui, width, "Minority Interest: ", &mut bridge.minority_interest);
+        changed |= float_field(ui, width, "Preferred Equity: ", &mut bridge.preferred_equity);
+        changed |= float_field(ui, width, "Non-Operating Assets: ", &mut bridge.non_operating_assets);
+        changed |= float_field(ui, width, "Diluted Shares: ", &mut bridge.diluted_shares);
+        changed |= float_field(ui, width, "Market Price: ", &mut bridge.market_price);
+
+        if changed {
+            self.cache = None;
+        }
+    }
+}
+
+/// One line of equity results under the DCF result.
+pub(super) fn equity_ui(ui: &mut egui::Ui, equity: &EquityValue) {
+    ui.horizontal(|ui| {
+        ui.strong(format!("Equity Value: {}", equity.equity_value));
+        match equity.per_share {
+            Some(per_share) => ui.strong(format!("Per Share: {per_share}")),
+            None => ui.label("Per Share: enter a share count"),
+        };
+        if let (Some(upside), Some(margin)) = (equity.upside, equity.margin_of_safety) {
+            let color = if upside >= 0.0 { egui::Color32::DARK_GREEN } else { ui.visuals().error_fg_color };
+            ui.colored_label(color, format!("Upside: {:.1}%", upside * 100.0));
+            ui.label(format!("Margin of Safety: {:.1}%", margin * 100.0));
+        }
+    });
+}
@@ -0,0 +1,55 @@
+use eframe::egui;
+
+use super::AppState;
+
+impl AppState {
+    /// Calendar section of the left panel: valuation date and explicit dates.
+    pub(super) fn calendar_ui(&mut self, ui: &mut egui::Ui) {
+        let error = self.cache.as_ref().and_then(|v| v.calendar_error.clone());
+        let state = &mut self.state;
+        let mut changed = ui.checkbox(&mut state.calendar.enabled, "Use calendar dates")
+            .on_hover_text("Each period is discounted over its exact year fraction (XNPV)")
+            .changed();
+
+        ui.horizontal(|ui| {
+            ui.label("Valuation Date: ");
+            changed |= ui.add(
+                egui::TextEdit::singleline(&mut state.calendar.valuation_date)
+                    .desired_width(90.0)
+                    .hint_text("YYYY-MM-DD"),
+            ).changed();
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Add Date").on_hover_text("Explicit date of the next period; later periods follow the period length").clicked() {
+                state.calendar.dates.push(String::new());
+                changed = true;
+            }
+            if ui.button("Delete Date").clicked() {
+                state.calendar.dates.pop();
+                changed = true;
+            }
+        });
+        egui::Grid::new("calendar_grid")
+            .spacing([8.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, date) in state.calendar.dates.iter_mut().enumerate() {
+                    ui.label(format!("t = {}", i + 1));
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(date)
+                            .desired_width(90.0)
+                            .hint_text("YYYY-MM-DD"),
+                    ).changed();
+                    ui.end_row();
+                }
+            });
+
+        if let Some(err) = error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {err}"));
+        }
+
+        if changed {
+            self.cache = None;
+        }
+    }
+}
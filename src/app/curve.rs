@@ -0,0 +1,147 @@
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints, Points};
+use futures::channel::oneshot;
+
+use crate::engine::{interpolate, zero_rate, CurveKind, CurvePoint, Interpolation, YieldCurve};
+
+#[cfg(not(target_arch = "wasm32"))]
+use rfd::FileDialog;
+#[cfg(target_arch = "wasm32")]
+use rfd::AsyncFileDialog;
+
+use super::AppState;
+
+impl AppState {
+    fn load_curve_file(&mut self) {
+
+        let (tx_popup, rx_popup) = oneshot::channel::<(String, String)>();
+        self.pending_popup = Some(rx_popup);
+
+        let (tx_curve, rx_curve) = oneshot::channel::<Vec<CurvePoint>>();
+        self.pending_curve = Some(rx_curve);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = FileDialog::new()
+            .add_filter("csv", &["csv", "txt"])
+            .pick_file()
+        {
+            let _ = match std::fs::read_to_string(path) {
+                Ok(v) => match YieldCurve::parse_csv(&v) {
+                    Ok(points) => {
+                        let _ = tx_curve.send(points);
+                        tx_popup.send(("Successfully Loaded".into(), "Successfully loaded the curve without any error".into()))
+                    },
+                    Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading curve: {e}"))),
+                },
+                Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading curve: {e}"))),
+            };
+        }
+
+        #[cfg(target_arch = "wasm32")] {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(handle) = AsyncFileDialog::new()
+                    .add_filter("csv", &["csv", "txt"])
+                    .pick_file()
+                    .await
+                {
+                    let text = String::from_utf8_lossy(&handle.read().await).into_owned();
+                    let _ = match YieldCurve::parse_csv(&text) {
+                        Ok(points) => {
+                            let _ = tx_curve.send(points);
+                            tx_popup.send(("Successfully Loaded".into(), "Successfully loaded the curve without any error".into()))
+                        },
+                        Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading curve: {e}"))),
+                    };
+                }
+            });
+        }
+    }
+
+    /// Yield curve section of the left panel: settings, points table and a plot of zero rates.
+    pub(super) fn curve_ui(&mut self, ui: &mut egui::Ui) {
+        let error = self.cache.as_ref().and_then(|v| v.curve_error);
+        let curve = &mut self.state.yield_curve;
+        let mut changed = ui.checkbox(&mut curve.enabled, "Discount with yield curve").changed();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("curve_kind")
+                .selected_text(curve.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in CurveKind::ALL {
+                        changed |= ui.selectable_value(&mut curve.kind, kind, kind.label()).changed();
+                    }
+                });
+            egui::ComboBox::from_id_salt("curve_interpolation")
+                .selected_text(curve.interpolation.label())
+                .show_ui(ui, |ui| {
+                    for interpolation in Interpolation::ALL {
+                        changed |= ui.selectable_value(&mut curve.interpolation, interpolation, interpolation.label()).changed();
+                    }
+                });
+        });
+
+        let mut load_clicked = false;
+        ui.horizontal(|ui| {
+            if ui.button("Add Point").clicked() {
+                curve.points.push(CurvePoint::default());
+                changed = true;
+            }
+            if ui.button("Delete Point").clicked() {
+                curve.points.pop();
+                changed = true;
+            }
+            load_clicked = ui.button("Load CSV").on_hover_text("Lines of `tenor,value`").clicked();
+        });
+
+        egui::Grid::new("curve_grid")
+            .spacing([8.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for point in &mut curve.points {
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut point.tenor)
+                            .desired_width(60.0)
+                            .hint_text("Tenor"),
+                    ).changed();
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut point.value)
+                            .desired_width(80.0)
+                            .hint_text(curve.kind.label()),
+                    ).changed();
+                    ui.end_row();
+                }
+            });
+
+        if let Some(err) = error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {err}"));
+        }
+
+        let nodes = curve.discount_factors();
+        if nodes.len() > 1 {
+            let last = nodes.last().unwrap().0;
+            let line: PlotPoints = (1..=200)
+                .map(|i| {
+                    let t = last * 1.2 * i as f64 / 200.0;
+                    [t, zero_rate(interpolate(&nodes, curve.interpolation, t), t)]
+                })
+                .collect();
+            let points: PlotPoints = nodes.iter()
+                .skip(1)
+                .map(|&(t, df)| [t, zero_rate(df, t)])
+                .collect();
+            Plot::new("curve_plot")
+                .height(150.0)
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new("Zero Rate", line));
+                    plot_ui.points(Points::new("Tenors", points).radius(3.0));
+                });
+        }
+
+        if changed {
+            self.cache = None;
+        }
+        if load_clicked {
+            self.load_curve_file();
+        }
+    }
+}
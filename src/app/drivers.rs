@@ -0,0 +1,39 @@
+use eframe::egui;
+
+use crate::engine::{Driver, Row};
+
+use super::{rows_grid, AppState};
+
+impl AppState {
+    /// Driver section of the left panel: one piecewise row table per free-cash-flow driver.
+    pub(super) fn drivers_ui(&mut self, ui: &mut egui::Ui) {
+        let series = self.cache.as_ref().and_then(|v| v.drivers.clone());
+        let drivers = &mut self.state.drivers;
+        let mut changed = ui.checkbox(&mut drivers.enabled, "Build cash flow from drivers")
+            .on_hover_text("Revenue * EBIT Margin * (1 - Tax Rate) + D&A - Capex - ΔNWC replaces the rows above")
+            .changed();
+
+        for driver in Driver::ALL {
+            let rows = drivers.rows_mut(driver);
+            let errors = series.as_ref().map(|s| s.row_errors(driver)).unwrap_or_default();
+            egui::CollapsingHeader::new(driver.label()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        rows.push(Row::default());
+                        changed = true;
+                    }
+                    if ui.button("Delete").clicked() {
+                        rows.pop();
+                        changed = true;
+                    }
+                });
+
+                changed |= rows_grid(ui, ("driver_grid", driver.label()), rows, errors, driver == Driver::Revenue).inner;
+            });
+        }
+
+        if changed {
+            self.cache = None;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use eframe::egui;
+
+use crate::engine::{GoalSeekError, Root};
+
+use super::{signed_float_field, AppState};
+
+impl AppState {
+    /// Goal seek section of the left panel.
+    pub(super) fn goal_seek_ui(&mut self, ui: &mut egui::Ui) {
+        let width = ui.available_width();
+        let market_ev = self.state.bridge.market_enterprise_value();
+        let goal_seek = &mut self.state.goal_seek;
+        let mut changed = ui.horizontal(|ui| {
+            ui.set_width(width);
+            ui.label("Market Value (EV): ");
+            let hint = market_ev.map(|v| format!("{v:.2} from bridge")).unwrap_or_default();
+            let changed = ui.add(egui::TextEdit::singleline(&mut goal_seek.market_value).hint_text(hint))
+                .on_hover_text("Empty uses market price × diluted shares bridged back to enterprise value")
+                .changed();
+            if changed {
+                let mut dot_counter: usize = 0;
+                goal_seek.market_value.retain(|c| super::retain_float(c, &mut dot_counter));
+            }
+            changed
+        }).inner;
+        changed |= signed_float_field(ui, width, "Discount Search From: ", &mut goal_seek.discount_min);
+        changed |= signed_float_field(ui, width, "Discount Search To: ", &mut goal_seek.discount_max);
+        changed |= signed_float_field(ui, width, "Growth Search From: ", &mut goal_seek.growth_min);
+        changed |= signed_float_field(ui, width, "Growth Search To: ", &mut goal_seek.growth_max);
+        changed |= ui.horizontal(|ui| {
+            ui.set_width(width);
+            ui.label("Row to Scale: ");
+            let changed = ui.add(egui::TextEdit::singleline(&mut goal_seek.row).hint_text("none"))
+                .on_hover_text("Solves for a multiplier on this row's expression")
+                .changed();
+            if changed {
+                goal_seek.row.retain(|c| c.is_ascii_digit());
+            }
+            changed
+        }).inner;
+        if !goal_seek.row.is_empty() {
+            changed |= signed_float_field(ui, width, "Multiplier Search From: ", &mut goal_seek.multiplier_min);
+            changed |= signed_float_field(ui, width, "Multiplier Search To: ", &mut goal_seek.multiplier_max);
+        }
+
+        if changed {
+            self.cache = None;
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Solve").on_hover_text("Re-runs the valuation a few hundred times per solved value").clicked() {
+                self.implied_discount = Some(self.state.implied_discount());
+                self.implied_growth = Some(self.state.implied_growth());
+                self.implied_multiplier = (!self.state.goal_seek.row.is_empty()).then(|| self.state.implied_multiplier());
+            }
+            if self.implied_discount.is_none() {
+                ui.label("Press Solve after changing the model");
+            }
+        });
+    }
+}
+
+/// One line with a solved value, or why it could not be solved.
+pub(super) fn implied_ui(ui: &mut egui::Ui, label: &str, result: &Result<Root, GoalSeekError>) {
+    match result {
+        Ok(root) => {
+            ui.strong(format!("{label}: {:.6}", root.value))
+                .on_hover_text(format!("Converged in {} bisection steps", root.iterations));
+        },
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {label}: {e}"));
+        },
+    }
+}
@@ -0,0 +1,933 @@
+use eframe::egui::{self, Align, Id, ScrollArea, Window};
+use egui_extras::{Column, TableBuilder};
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoints};
+use futures::channel::oneshot;
+
+use crate::engine::{
+    CurvePoint, Driver, ExpectedValuation, Frequency, GoalSeekError, ModelKind, MonteCarloError, MonteCarloRun, Root, Row,
+    RowError, SensitivityTable, Simulation, StateData, TerminalMethod, TimeBase, Timing, Tornado, Valuation, ValuationMode,
+};
+
+mod bridge;
+mod calendar;
+mod curve;
+mod drivers;
+mod goalseek;
+mod montecarlo;
+mod parameters;
+mod scenario;
+mod sensitivity;
+mod series;
+mod tornado;
+mod wacc;
+
+
+#[cfg(not(target_arch = "wasm32"))]
+use rfd::FileDialog;
+#[cfg(target_arch = "wasm32")]
+use rfd::AsyncFileDialog;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use web_sys::HtmlCanvasElement;
+
+fn retain_float(c: char, dots: &mut usize) -> bool {
+    if c == '.' {
+        *dots += 1;
+        *dots <= 1
+    } else {
+        c.is_ascii_digit()
+    }
+}
+
+/// Like [`retain_float`] over the whole value, but keeps a leading `-`.
+fn retain_signed_float(value: &mut String) {
+    let negative = value.trim_start().starts_with('-');
+    let mut dot_counter: usize = 0;
+    value.retain(|c| retain_float(c, &mut dot_counter));
+    if negative {
+        value.insert(0, '-');
+    }
+}
+
+/// Labeled numeric input in the left panel; returns `true` when the value changed.
+fn float_field(ui: &mut egui::Ui, width: f32, label: &str, value: &mut String) -> bool {
+    ui.horizontal(|ui| {
+        ui.set_width(width);
+        ui.label(label);
+        let changed = ui.text_edit_singleline(value).changed();
+        if changed {
+            let mut dot_counter: usize = 0;
+            value.retain(|c| retain_float(c, &mut dot_counter));
+        }
+        changed
+    }).inner
+}
+
+/// [`float_field`] for values that may be negative.
+fn signed_float_field(ui: &mut egui::Ui, width: f32, label: &str, value: &mut String) -> bool {
+    ui.horizontal(|ui| {
+        ui.set_width(width);
+        ui.label(label);
+        let changed = ui.text_edit_singleline(value).changed();
+        if changed {
+            retain_signed_float(value);
+        }
+        changed
+    }).inner
+}
+
+fn integer_field(ui: &mut egui::Ui, width: f32, label: &str, value: &mut String) -> bool {
+    ui.horizontal(|ui| {
+        ui.set_width(width);
+        ui.label(label);
+        let changed = ui.text_edit_singleline(value).changed();
+        if changed {
+            value.retain(|c| c.is_ascii_digit());
+        }
+        changed
+    }).inner
+}
+
+/// Model kind dropdown of a row; `Auto` shows the kind it detected. Returns `true` when changed.
+fn model_kind_combo(ui: &mut egui::Ui, id: impl std::hash::Hash, row: &mut Row) -> bool {
+    let selected = match (row.kind, row.model_kind()) {
+        (ModelKind::Auto, Some(kind)) => format!("Auto ({})", kind.label()),
+        (kind, _) => kind.label().to_owned(),
+    };
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for kind in ModelKind::ALL {
+                changed |= ui.selectable_value(&mut row.kind, kind, kind.label()).changed();
+            }
+        });
+    changed
+}
+
+/// Piecewise row table shared by the main rows, the drivers and the named series:
+/// `start ~ end`, the expression, its model kind, the segment's own discount rate when
+/// `discount` is set, and the row's error.
+///
+/// The inner value is `true` when a row changed.
+fn rows_grid(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    rows: &mut [Row],
+    errors: &[Option<RowError>],
+    discount: bool,
+) -> egui::InnerResponse<bool> {
+    let id = Id::new(id);
+    egui::Grid::new(id)
+        .spacing([8.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            let mut changed = false;
+            let mut prev_start = String::from("0");   // first row means start value
+
+            for (i, row) in rows.iter_mut().enumerate() {
+                ui.label(&prev_start);
+
+                ui.label(" ~ ");
+
+                if ui.add(
+                    egui::TextEdit::singleline(&mut row.end)
+                        .desired_width(60.0)
+                        .hint_text("End"),
+                ).changed() {
+                    changed = true;
+
+                    row.end.retain(|c| c.is_ascii_digit());
+                }
+
+                changed |= ui.add(
+                    egui::TextEdit::singleline(&mut row.expr)
+                        .hint_text("Expression")
+                ).changed();
+
+                changed |= model_kind_combo(ui, id.with(("kind", i)), row);
+
+                if discount {
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut row.discount)
+                            .desired_width(80.0)
+                            .hint_text("Discount"),
+                    ).on_hover_text("Discount rate of this segment, constant or in t; empty uses the global rate")
+                        .changed();
+                }
+
+                if let Some(Some(err)) = errors.get(i) {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {err}"));
+                }
+
+                ui.end_row();
+
+                prev_start = row.end.clone();
+            }
+
+            changed
+        })
+}
+
+#[derive(Default)]
+pub struct AppState {
+    state: StateData,
+
+    popup_state: bool,
+    popup_title: String,
+    popup_msg: String,
+
+    pending_popup: Option<oneshot::Receiver<(String, String)>>,
+    pending_state: Option<oneshot::Receiver<StateData>>,
+    pending_curve: Option<oneshot::Receiver<Vec<CurvePoint>>>,
+
+    cache: Option<Valuation>,
+    /// Valuation of every scenario, when there are any; rebuilt whenever `cache` is.
+    expected: Option<ExpectedValuation>,
+    /// Discount rate implied by the market value, solved on demand; cleared whenever `cache` is.
+    implied_discount: Option<Result<Root, GoalSeekError>>,
+    implied_growth: Option<Result<Root, GoalSeekError>>,
+    /// Only when a row to scale is chosen.
+    implied_multiplier: Option<Result<Root, GoalSeekError>>,
+
+    sensitivity_open: bool,
+    /// Computed on demand; cleared whenever `cache` is, so it never shows a stale scenario.
+    sensitivity: Option<SensitivityTable>,
+    tornado_open: bool,
+    /// Computed on demand and cleared together with `sensitivity`.
+    tornado: Option<Tornado>,
+    monte_carlo_open: bool,
+    /// Simulation being run, a chunk of runs per frame.
+    monte_carlo_run: Option<MonteCarloRun>,
+    /// Last Monte Carlo run; cleared, and a running one cancelled, whenever `cache` is.
+    simulation: Option<Result<Simulation, MonteCarloError>>,
+}
+
+impl AppState {
+    fn push_row(&mut self) {
+        self.state.rows.push(Row::default());
+        self.cache = None;
+    }
+
+    fn pop_row(&mut self) {
+        self.state.rows.pop();
+        self.cache = None;
+    }
+
+    fn save_file(&mut self) {
+
+        self.state.store_scenario();
+        let state = serde_json::to_string(&self.state).unwrap();
+
+        let (tx, rx) = oneshot::channel::<(String, String)>();
+        self.pending_popup = Some(rx);
+
+        #[cfg(not(target_arch = "wasm32"))] {
+            if let Some(path) = FileDialog::new()
+                .add_filter("json", &["json"])
+                .save_file()
+            {
+                let _ = match std::fs::write(path, state) {
+                    Ok(_) => tx.send(("Successfully Saved".into(), "Successfully saved without any error".into())),
+                    Err(e) => tx.send(("Error Occurred".into(), format!("Error while saving: {e}"))),
+                };
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")] {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(handle) = AsyncFileDialog::new()
+                    .set_file_name("state.json")
+                    .save_file()
+                    .await
+                {
+                    let _ = match handle.write(state.as_bytes()).await {
+                        Ok(_) => tx.send(("Successfully Saved".into(), "Successfully saved without any error".into())),
+                        Err(e) => tx.send(("Error Occurred".into(), format!("Error while saving: {e}"))),
+                    };
+                }
+            });
+        }
+    }
+
+    fn load_file(&mut self) {
+
+        let (tx_popup, rx_popup) = oneshot::channel::<(String, String)>();
+        self.pending_popup = Some(rx_popup);
+
+        let (tx_state, rx_state) = oneshot::channel::<StateData>();
+        self.pending_state = Some(rx_state);
+
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = FileDialog::new()
+            .add_filter("json", &["json"])
+            .pick_file()
+        {
+            let _ = match std::fs::read(path) {
+                Ok(v) => match serde_json::from_slice::<StateData>(&v) {
+                    Ok(state) => {
+                        let _ = tx_state.send(state);
+                        tx_popup.send(("Successfully Loaded".into(), "Successfully loaded without any error".into()))
+                    },
+                    Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading: {e}"))),
+                },
+                Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading: {e}"))),
+            };
+        }
+
+        #[cfg(target_arch = "wasm32")] {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(handle) = AsyncFileDialog::new()
+                    .add_filter("json", &["json"])
+                    .pick_file()
+                    .await
+                {
+                    let _ = match serde_json::from_slice::<StateData>(&handle.read().await) {
+                        Ok(state) => {
+                            let _ = tx_state.send(state);
+                            tx_popup.send(("Successfully Loaded".into(), "Successfully loaded without any error".into()))
+                        },
+                        Err(e) => tx_popup.send(("Error Occurred".into(), format!("Error while loading: {e}"))),
+                    };
+                }
+            });
+        }
+        
+    }
+
+    fn show_popup(&mut self, title: String, msg: String) {
+        self.popup_state = true;
+        self.popup_title = title;
+        self.popup_msg = msg;
+    }
+
+    fn close_popup(&mut self) {
+        self.popup_state = false;
+    }
+}
+
+/* ───────── egui App implementation ───────── */
+impl eframe::App for AppState {
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+
+        if let Some(rx) = &mut self.pending_popup {
+            match rx.try_recv() {
+                Ok(Some((title, msg))) => {
+                    self.show_popup(title, msg);
+                },
+                Err(e) => {
+                    log::error!("Error while loading popup: {e}");
+                },
+                _ => {},
+            }
+            self.pending_popup = None;
+        }
+
+        if let Some(rx) = &mut self.pending_state {
+            match rx.try_recv() {
+                Ok(Some(state)) => {
+                    self.state = state;
+                    self.state.apply_wacc();
+                },
+                Err(e) => {
+                    log::error!("Error while loading state: {e}");
+                },
+                _ => {},
+            }
+            self.pending_state = None;
+            self.cache = None;
+        }
+
+        if let Some(rx) = &mut self.pending_curve {
+            match rx.try_recv() {
+                Ok(Some(points)) => {
+                    self.state.yield_curve.points = points;
+                },
+                Err(e) => {
+                    log::error!("Error while loading curve: {e}");
+                },
+                _ => {},
+            }
+            self.pending_curve = None;
+            self.cache = None;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::A)) {
+            self.push_row();
+        };
+
+        if ctx.input(|i| i.key_pressed(egui::Key::D)) {
+            self.pop_row();
+        };
+
+        if ctx.input(|i: &egui::InputState| i.key_pressed(egui::Key::S)) {
+            self.save_file();
+        };
+
+        if ctx.input(|i| i.key_pressed(egui::Key::L)) {
+            self.load_file();
+        };
+
+        if self.cache.is_none() {
+            self.cache = Some(self.state.evaluate());
+            self.sensitivity = None;
+            self.tornado = None;
+            self.monte_carlo_run = None;
+            self.simulation = None;
+            self.expected = (!self.state.scenarios.is_empty()).then(|| self.state.expected_valuation());
+            // solved again on demand, every solve re-runs hundreds of valuations
+            self.implied_discount = None;
+            self.implied_growth = None;
+            self.implied_multiplier = None;
+        }
+        let row_errors = self.cache.as_ref().map(|v| v.row_errors.clone()).unwrap_or_default();
+
+
+        egui::SidePanel::left(Id::new("leftside")).show(ctx, |ui| {
+            // 1) Control Buttons
+            ui.horizontal(|ui| {
+                if ui.button("Add").clicked() {
+                    self.push_row();
+                }
+                if ui.button("Delete").clicked() {
+                    self.pop_row();
+                }
+                if ui.button("Save").clicked() {
+                    self.save_file();
+                }
+                if ui.button("Load").clicked() {
+                    self.load_file();
+                }
+                ui.toggle_value(&mut self.sensitivity_open, "Sensitivity");
+                ui.toggle_value(&mut self.tornado_open, "Tornado");
+                ui.toggle_value(&mut self.monte_carlo_open, "Monte Carlo");
+            });
+            
+            ui.separator();
+
+
+            // 2) Draw Rows
+            let grid = rows_grid(ui, "ranges_grid", &mut self.state.rows, &row_errors, true);
+            if grid.inner {
+                self.cache = None;
+            }
+
+            // the terminal row follows the selected terminal value method
+            let prev_start = self.state.rows.last().map(|r| r.end.clone()).unwrap_or_else(|| "0".into());
+            let terminal = &self.state.terminal;
+            ui.horizontal(|ui| {
+                ui.label(&prev_start);
+                ui.label(" ~ ");
+                match terminal.method {
+                    TerminalMethod::PerpetualGrowth if !terminal.horizon.is_empty() => {
+                        ui.label(format!("+{}", terminal.horizon));
+                    },
+                    TerminalMethod::ExitMultiple => {
+                        ui.label("exit");
+                    },
+                    _ => {
+                        ui.label("∞");
+                    },
+                }
+                let label = match terminal.method {
+                    TerminalMethod::PerpetualGrowth => format!(" ^ t * y[{prev_start}]"),
+                    TerminalMethod::ExitMultiple => {
+                        let metric = match terminal.exit_metric.as_str() {
+                            "" => format!("y[{prev_start}]"),
+                            metric => metric.to_owned(),
+                        };
+                        ui.label(format!("{} * {metric}", terminal.exit_multiple));
+                        return;
+                    },
+                    TerminalMethod::HModel => format!(
+                        " ^ t * y[{prev_start}], fading from {} (half-life {})",
+                        terminal.h_model_growth, terminal.h_model_half_life
+                    ),
+                    TerminalMethod::Fade => format!(
+                        " ^ t * y[{prev_start}], after fading from {} over {} periods",
+                        terminal.fade_growth, terminal.fade_periods
+                    ),
+                };
+                if ui.add(
+                    egui::TextEdit::singleline(&mut self.state.growth)
+                        .desired_width(60.0)
+                        .hint_text("Growth"),
+                ).changed() {
+                    self.cache = None;
+
+                    let mut dot_counter: usize = 0;
+                    self.state.growth.retain(|c| retain_float(c, &mut dot_counter));
+                }
+                ui.label(label);
+            });
+
+            let grid_width = grid.response.rect.right() - grid.response.rect.left();
+
+            // 3) discount rate
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Discount Rate (e.g. WACC, per year): ");
+                if ui.add_enabled(!self.state.wacc.enabled, egui::TextEdit::singleline(&mut self.state.discount))
+                    .on_disabled_hover_text("Driven by the WACC builder")
+                    .changed()
+                {
+                    self.cache = None;
+
+                    let mut dot_counter: usize = 0;
+                    self.state.discount.retain(|c| retain_float(c, &mut dot_counter));
+                }
+            });
+
+            // 4) period length; discount and growth are per year
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Period Length: ");
+                egui::ComboBox::from_id_salt("frequency")
+                    .selected_text(self.state.frequency.label())
+                    .show_ui(ui, |ui| {
+                        for frequency in Frequency::ALL {
+                            if ui.selectable_value(&mut self.state.frequency, frequency, frequency.label()).changed() {
+                                self.cache = None;
+                            }
+                        }
+                    });
+                if self.state.frequency != Frequency::Annual {
+                    let discount = self.state.per_period(self.state.discount.parse().unwrap_or(1.0));
+                    let growth = self.state.per_period(self.state.growth.parse().unwrap_or(1.0));
+                    ui.label(format!("per {}: discount {discount:.6}, growth {growth:.6}", self.state.frequency.period_label()))
+                        .on_hover_text("Annual rates converted with compounding");
+                }
+            });
+
+            // 5) valuation mode and discounting convention
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Valuation Mode: ");
+                egui::ComboBox::from_id_salt("valuation_mode")
+                    .selected_text(self.state.valuation_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in ValuationMode::ALL {
+                            if ui.selectable_value(&mut self.state.valuation_mode, mode, mode.label()).changed() {
+                                self.cache = None;
+                            }
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                if self.state.valuation_mode != ValuationMode::Discrete {
+                    ui.disable();
+                }
+                ui.label("Discounting Convention: ");
+                egui::ComboBox::from_id_salt("timing")
+                    .selected_text(self.state.timing.label())
+                    .show_ui(ui, |ui| {
+                        for timing in Timing::ALL {
+                            if ui.selectable_value(&mut self.state.timing, timing, timing.label()).changed() {
+                                self.cache = None;
+                            }
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Time Variable t: ");
+                egui::ComboBox::from_id_salt("time_base")
+                    .selected_text(self.state.time_base.label())
+                    .show_ui(ui, |ui| {
+                        for base in TimeBase::ALL {
+                            if ui.selectable_value(&mut self.state.time_base, base, base.label()).changed() {
+                                self.cache = None;
+                            }
+                        }
+                    });
+            }).response.on_hover_text("Whether t restarts in every row; T always counts from the valuation date");
+
+            // 6) terminal value method
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Terminal Value Method: ");
+                egui::ComboBox::from_id_salt("terminal_method")
+                    .selected_text(self.state.terminal.method.label())
+                    .show_ui(ui, |ui| {
+                        for method in TerminalMethod::ALL {
+                            if ui.selectable_value(&mut self.state.terminal.method, method, method.label()).changed() {
+                                self.cache = None;
+                            }
+                        }
+                    });
+            });
+            let terminal = &mut self.state.terminal;
+            let changed = match terminal.method {
+                TerminalMethod::PerpetualGrowth => ui.horizontal(|ui| {
+                    ui.set_width(grid_width);
+                    ui.label("Terminal Horizon (periods): ");
+                    let changed = ui.add(
+                        egui::TextEdit::singleline(&mut terminal.horizon)
+                            .hint_text("∞"),
+                    ).on_hover_text("Leave empty for a perpetuity; set it when growth is not below the discount rate")
+                        .changed();
+                    if changed {
+                        terminal.horizon.retain(|c| c.is_ascii_digit());
+                    }
+                    changed
+                }).inner,
+                TerminalMethod::ExitMultiple => {
+                    float_field(ui, grid_width, "Exit Multiple: ", &mut terminal.exit_multiple)
+                        | float_field(ui, grid_width, "Metric (empty = last cash flow): ", &mut terminal.exit_metric)
+                },
+                TerminalMethod::HModel => {
+                    float_field(ui, grid_width, "Initial Growth: ", &mut terminal.h_model_growth)
+                        | float_field(ui, grid_width, "Half-Life (periods): ", &mut terminal.h_model_half_life)
+                },
+                TerminalMethod::Fade => {
+                    float_field(ui, grid_width, "Initial Growth: ", &mut terminal.fade_growth)
+                        | integer_field(ui, grid_width, "Fade Periods: ", &mut terminal.fade_periods)
+                },
+            };
+            if changed {
+                self.cache = None;
+            }
+
+            // 7) legacy terminal value
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                if ui.checkbox(&mut self.state.legacy_terminal_value, "Legacy: add undiscounted Terminal Value")
+                    .on_hover_text("Reproduces results of files saved before the terminal value was discounted")
+                    .changed()
+                {
+                    self.cache = None;
+                }
+            });
+
+            // 8) step size
+            ui.horizontal(|ui| {
+                ui.set_width(grid_width);
+                ui.label("Step Size for ODE Solver: ");
+                if ui.text_edit_singleline(&mut self.state.ode_step_size).changed() {
+                    self.cache = None;
+
+                    let mut dot_counter: usize = 0;
+                    self.state.ode_step_size.retain(|c| retain_float(c, &mut dot_counter));
+                }
+            });
+
+            ui.separator();
+
+            // 9) optional sections
+            ScrollArea::vertical()
+                .id_salt("left_sections")
+                .show(ui, |ui| {
+                    ui.set_width(grid_width);
+                    egui::CollapsingHeader::new("Parameters").show(ui, |ui| self.parameters_ui(ui));
+                    egui::CollapsingHeader::new("Named Series").show(ui, |ui| self.series_ui(ui));
+                    egui::CollapsingHeader::new("Scenarios").show(ui, |ui| self.scenario_ui(ui));
+                    egui::CollapsingHeader::new("Free Cash Flow Drivers").show(ui, |ui| self.drivers_ui(ui));
+                    egui::CollapsingHeader::new("Calendar").show(ui, |ui| self.calendar_ui(ui));
+                    egui::CollapsingHeader::new("WACC Builder").show(ui, |ui| self.wacc_ui(ui));
+                    egui::CollapsingHeader::new("Yield Curve").show(ui, |ui| self.curve_ui(ui));
+                    egui::CollapsingHeader::new("Equity Bridge").show(ui, |ui| self.bridge_ui(ui));
+                    egui::CollapsingHeader::new("Goal Seek").show(ui, |ui| self.goal_seek_ui(ui));
+                });
+        });
+        egui::TopBottomPanel::bottom(Id::new("tornado"))
+            .resizable(true)
+            .default_height(240.0)
+            .show_animated(ctx, self.tornado_open, |ui| self.tornado_ui(ui));
+        egui::CentralPanel::default().show(ctx, |ui| {
+
+            ui.horizontal(|ui| {
+                ui.heading("Cash Flow Expectation");
+                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                    ui.checkbox(&mut self.state.use_log_scale, "Log Scale");
+                });
+            });
+
+            if let Some(valuation) = &self.cache {
+                let use_log_scale = self.state.use_log_scale;
+                let to_points = |cashflow: &mut dyn Iterator<Item = f64>| -> PlotPoints {
+                    cashflow.enumerate().map(|(x, y)| {
+                        if use_log_scale {
+                            [x as f64, f64::max(0.0, y.log10())]
+                        } else {
+                            [x as f64, y]
+                        }
+                    }).collect()
+                };
+                let points = to_points(&mut valuation.dcf.iter().map(|d| d.cashflow));
+                let active = self.state.scenarios.get(self.state.active_scenario);
+                Plot::new("my_plot")
+                    .view_aspect(2.0)
+                    .legend(Legend::default())
+                    .show(ui, |plot_ui| {
+                        // the other scenarios, overlaid behind the live one
+                        if let Some(expected) = &self.expected {
+                            for (i, scenario) in expected.scenarios.iter().enumerate() {
+                                if i != self.state.active_scenario {
+                                    let points = to_points(&mut scenario.cashflow.iter().copied());
+                                    plot_ui.line(Line::new(&scenario.name, points).style(LineStyle::dashed_loose()));
+                                }
+                            }
+                        }
+                        let name = active.map(|s| s.name.as_str()).unwrap_or("Cash Flow Expectation");
+                        plot_ui.line(Line::new(name, points));
+                    });
+
+                ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .max_height(ui.available_height() - 20.0 - 18.0 * (TerminalMethod::ALL.len() + 4) as f32)
+                    .show(ui, |ui| {
+                        // driver columns sit between t and the resulting cash-flow
+                        let drivers = valuation.drivers.as_ref();
+                        let driver_columns = if drivers.is_some() { Driver::ALL.len() } else { 0 };
+                        // named series other than the discounted one, before the drivers
+                        let series: Vec<_> = valuation.series.iter()
+                            .filter(|s| s.name != self.state.discounted_series)
+                            .collect();
+                        // run-rate per year of sub-annual cash-flows
+                        let frequency = self.state.frequency;
+                        let annualized = frequency != Frequency::Annual;
+                        TableBuilder::new(ui)
+                            .striped(true)
+                            .columns(Column::remainder(), 4 + series.len() + driver_columns + annualized as usize)
+                            .header(22.0, |mut header| {
+                                header.col(|ui| {
+                                    if valuation.dates.is_some() {
+                                        ui.strong("Date");
+                                    } else if annualized {
+                                        ui.strong(format!("t ({})", frequency.period_label()));
+                                    } else {
+                                        ui.strong("t");
+                                    }
+                                });
+                                for s in &series {
+                                    header.col(|ui| { ui.strong(&s.name); });
+                                }
+                                if drivers.is_some() {
+                                    for driver in Driver::ALL {
+                                        header.col(|ui| { ui.strong(driver.label()); });
+                                    }
+                                }
+                                header.col(|ui| { ui.strong("Cashflow");  });
+                                if annualized {
+                                    header.col(|ui| { ui.strong("Annualized"); });
+                                }
+                                header.col(|ui| { ui.strong("UNIT DCF");  });
+                                header.col(|ui| { ui.strong("Sum of DCF");  });
+                            })
+                            .body(|mut body| {
+                                for (t, &data) in valuation.dcf.iter().enumerate() {
+                                    body.row(16.0, |mut row| {
+                                        let date = valuation.dates.as_ref().and_then(|d| d.get(t));
+                                        row.col(|ui| { ui.label(date.map(|d| d.to_string()).unwrap_or(t.to_string())); });
+                                        for s in &series {
+                                            let value = s.values.get(t).map(|v| v.to_string()).unwrap_or_default();
+                                            row.col(|ui| { ui.label(value); });
+                                        }
+                                        if let Some(drivers) = drivers {
+                                            for driver in Driver::ALL {
+                                                let value = drivers.column(driver).get(t).copied().unwrap_or_default();
+                                                row.col(|ui| { ui.label(value.to_string()); });
+                                            }
+                                        }
+                                        row.col(|ui| { ui.label(data.cashflow.to_string()); });
+                                        if annualized {
+                                            let value = data.cashflow * frequency.periods_per_year() as f64;
+                                            row.col(|ui| { ui.label(value.to_string()); });
+                                        }
+                                        row.col(|ui| { ui.label(data.dcf_unit.to_string()); });
+                                        row.col(|ui| { ui.label(data.dcf_sum.to_string()); });
+                                    });
+                                }
+                            });
+                    });
+                
+                ui.horizontal(|ui| {
+                    match &valuation.terminal_value {
+                        Ok(tv) => {
+                            ui.strong(format!("Terminal Value: {} (PV: {})", tv.value, tv.pv));
+                        },
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {e}"));
+                        },
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                        match valuation.total {
+                            Some(total) => ui.heading(format!("DCF Result: {total}")),
+                            None => ui.heading("DCF Result: invalid assumptions"),
+                        };
+                    });
+                });
+                if let Some(equity) = &valuation.equity {
+                    bridge::equity_ui(ui, equity);
+                }
+                if self.implied_discount.is_some() {
+                    ui.horizontal(|ui| {
+                        let implied = [
+                            ("Implied Discount Rate", &self.implied_discount),
+                            ("Implied Growth", &self.implied_growth),
+                            ("Implied Row Multiplier", &self.implied_multiplier),
+                        ];
+                        for (label, result) in implied {
+                            if let Some(result) = result {
+                                goalseek::implied_ui(ui, label, result);
+                            }
+                        }
+                    });
+                }
+                if let Some(expected) = &self.expected {
+                    match expected.expected {
+                        Some(value) => ui.strong(format!("Expected DCF Result (probability-weighted): {value}")),
+                        None => ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            "⚠ Expected DCF Result: a weighted scenario is invalid or no weight is set",
+                        ),
+                    };
+                }
+
+                // cross-check of every terminal value method
+                egui::Grid::new("terminal_cross_check")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Method");
+                        ui.strong("Terminal Value");
+                        ui.strong("PV");
+                        ui.strong("DCF Result");
+                        ui.end_row();
+
+                        for method in TerminalMethod::ALL {
+                            if method == self.state.terminal.method {
+                                ui.strong(method.label());
+                            } else {
+                                ui.label(method.label());
+                            }
+                            match self.state.terminal_value_with(method, &valuation.discount_curve, &valuation.dcf) {
+                                Ok(tv) => {
+                                    ui.label(tv.value.to_string());
+                                    ui.label(tv.pv.to_string());
+                                    ui.label(self.state.dcf_result(&valuation.dcf, &tv).to_string());
+                                },
+                                Err(_) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, "invalid");
+                                    ui.label("-");
+                                    ui.label("-");
+                                },
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+               
+        });
+
+        let mut sensitivity_open = self.sensitivity_open;
+        Window::new("Sensitivity")
+            .open(&mut sensitivity_open)
+            .show(ctx, |ui| self.sensitivity_ui(ui));
+        self.sensitivity_open = sensitivity_open;
+
+        let mut monte_carlo_open = self.monte_carlo_open;
+        Window::new("Monte Carlo")
+            .open(&mut monte_carlo_open)
+            .show(ctx, |ui| self.monte_carlo_ui(ui));
+        self.monte_carlo_open = monte_carlo_open;
+
+        if self.popup_state {
+            Window::new(&self.popup_title)
+                .resizable([false; 2])
+                .show(ctx, |ui| {
+                    ui.label(&self.popup_msg);
+                    if ui.button("OK").clicked() {
+                        self.close_popup();
+                    }
+                });
+        }
+    }
+}
+
+
+/// Your handle to the web app from JavaScript.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone)]
+#[wasm_bindgen]
+pub struct WebHandle {
+    runner: eframe::WebRunner,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl WebHandle {
+    /// Installs a panic hook, then returns.
+    #[allow(clippy::new_without_default)]
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        // Redirect [`log`] message to `console.log` and friends:
+        eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+
+        Self {
+            runner: eframe::WebRunner::new(),
+        }
+    }
+
+    /// Call this once from JavaScript to start your app.
+    #[wasm_bindgen]
+    pub async fn start(&self, canvas: HtmlCanvasElement) -> Result<(), wasm_bindgen::JsValue> {
+        self.runner
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(|_| Ok(Box::new(AppState::default())),)
+            )
+            .await
+    }
+
+    // The following are optional:
+
+    /// Shut down eframe and clean up resources.
+    #[wasm_bindgen]
+    pub fn destroy(&self) {
+        self.runner.destroy();
+    }
+
+    /// The JavaScript can check whether or not your app has crashed:
+    #[wasm_bindgen]
+    pub fn has_panicked(&self) -> bool {
+        self.runner.has_panicked()
+    }
+
+    #[wasm_bindgen]
+    pub fn panic_message(&self) -> Option<String> {
+        self.runner.panic_summary().map(|s| s.message())
+    }
+
+    #[wasm_bindgen]
+    pub fn panic_callstack(&self) -> Option<String> {
+        self.runner.panic_summary().map(|s| s.callstack())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(start)]
+pub async fn wasm_start() -> Result<(), wasm_bindgen::JsValue> {
+
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id("egui_canvas"))
+        .expect("canvas tag with id='egui_canvas' not found")
+        .dyn_into::<HtmlCanvasElement>()
+        .expect("element is not a canvas");
+
+    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+
+    eframe::WebRunner::new().start(
+        canvas,
+        eframe::WebOptions::default(),
+        Box::new(|_| Ok(Box::<AppState>::default())),
+    )
+    .await
+}
\ No newline at end of file
@@ -0,0 +1,159 @@
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Plot, VLine};
+
+use crate::engine::{DistributionKind, Uncertainty};
+
+use super::AppState;
+
+/// Runs done per frame while a simulation is running.
+const RUNS_PER_FRAME: usize = 100;
+
+impl AppState {
+    /// Monte Carlo window: distributions, run settings, histogram and percentile bands.
+    pub(super) fn monte_carlo_ui(&mut self, ui: &mut egui::Ui) {
+        let assumptions = self.state.assumptions();
+        let inputs = &mut self.state.monte_carlo;
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Runs: ");
+            changed |= ui.add(egui::TextEdit::singleline(&mut inputs.runs).desired_width(80.0)).changed();
+            ui.label("Seed: ");
+            changed |= ui.add(egui::TextEdit::singleline(&mut inputs.seed).desired_width(80.0)).changed();
+            inputs.runs.retain(|c| c.is_ascii_digit());
+            inputs.seed.retain(|c| c.is_ascii_digit());
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Add Distribution").clicked() {
+                inputs.uncertainties.push(Uncertainty::default());
+                changed = true;
+            }
+            if ui.button("Delete Distribution").clicked() {
+                inputs.uncertainties.pop();
+                changed = true;
+            }
+        });
+
+        egui::Grid::new("monte_carlo_grid")
+            .spacing([8.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, u) in inputs.uncertainties.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_salt(("mc_assumption", i))
+                        .selected_text(u.assumption.label())
+                        .show_ui(ui, |ui| {
+                            for assumption in &assumptions {
+                                changed |= ui.selectable_value(&mut u.assumption, assumption.clone(), assumption.label()).changed();
+                            }
+                        });
+                    egui::ComboBox::from_id_salt(("mc_kind", i))
+                        .selected_text(u.kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in DistributionKind::ALL {
+                                changed |= ui.selectable_value(&mut u.kind, kind, kind.label()).changed();
+                            }
+                        });
+                    let mut param = |ui: &mut egui::Ui, value: &mut String, hint: &str| {
+                        changed |= ui.add(
+                            egui::TextEdit::singleline(value)
+                                .desired_width(60.0)
+                                .hint_text(hint),
+                        ).on_hover_text(hint)
+                            .changed();
+                    };
+                    if u.kind.is_moment_based() {
+                        param(ui, &mut u.mean, "Mean");
+                        param(ui, &mut u.std_dev, "Std Dev");
+                    } else {
+                        param(ui, &mut u.min, "Min");
+                        if u.kind != DistributionKind::Uniform {
+                            param(ui, &mut u.mode, "Mode");
+                        }
+                        param(ui, &mut u.max, "Max");
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if changed {
+            self.monte_carlo_run = None;
+            self.simulation = None;
+        }
+        if let Some(run) = &mut self.monte_carlo_run {
+            if run.step(RUNS_PER_FRAME) {
+                self.simulation = self.monte_carlo_run.take().map(|run| run.finish());
+            } else {
+                let cancel = ui.horizontal(|ui| {
+                    ui.add(egui::ProgressBar::new(run.progress() as f32).show_percentage().desired_width(200.0));
+                    ui.button("Cancel").clicked()
+                }).inner;
+                if cancel {
+                    self.monte_carlo_run = None;
+                } else {
+                    ui.ctx().request_repaint();
+                }
+                return;
+            }
+        }
+        if ui.button("Run").clicked() {
+            match self.state.start_monte_carlo() {
+                Ok(run) => {
+                    self.monte_carlo_run = Some(run);
+                    self.simulation = None;
+                    ui.ctx().request_repaint();
+                },
+                Err(e) => self.simulation = Some(Err(e)),
+            }
+        }
+
+        ui.separator();
+        match &self.simulation {
+            None => {
+                ui.label("Press Run to simulate");
+            },
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {e}"));
+            },
+            Some(Ok(sim)) => {
+                egui::Grid::new("monte_carlo_stats").show(ui, |ui| {
+                    ui.label("Mean");
+                    ui.strong(sim.mean.to_string());
+                    ui.label("Median");
+                    ui.strong(sim.median().to_string());
+                    ui.end_row();
+                    ui.label("P5 / P95");
+                    ui.label(format!("{} / {}", sim.percentile(5.0), sim.percentile(95.0)));
+                    ui.label("P25 / P75");
+                    ui.label(format!("{} / {}", sim.percentile(25.0), sim.percentile(75.0)));
+                    ui.end_row();
+                    ui.label("Valid Runs");
+                    ui.label(sim.results.len().to_string());
+                    ui.label("Invalid Runs");
+                    ui.label(sim.invalid.to_string());
+                    ui.end_row();
+                });
+
+                let histogram = sim.histogram(40);
+                let width = histogram.get(1).map(|b| b.0 - histogram[0].0).unwrap_or(1.0);
+                let bars: Vec<Bar> = histogram.iter()
+                    .map(|&(center, count)| Bar::new(center, count as f64).width(width))
+                    .collect();
+                let bands = [
+                    ("P5", sim.percentile(5.0)),
+                    ("P25", sim.percentile(25.0)),
+                    ("Median", sim.median()),
+                    ("P75", sim.percentile(75.0)),
+                    ("P95", sim.percentile(95.0)),
+                ];
+                Plot::new("monte_carlo_plot")
+                    .view_aspect(2.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(BarChart::new("DCF Result", bars));
+                        for (name, value) in bands {
+                            plot_ui.vline(VLine::new(name, value));
+                        }
+                    });
+            },
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use eframe::egui;
+
+use crate::engine::Parameter;
+
+use super::AppState;
+
+impl AppState {
+    /// Parameter section of the left panel: named values the row expressions can use.
+    pub(super) fn parameters_ui(&mut self, ui: &mut egui::Ui) {
+        let errors = self.state.parameter_errors();
+        let parameters = &mut self.state.parameters;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("Add Parameter").clicked() {
+                parameters.push(Parameter::default());
+                changed = true;
+            }
+            if ui.button("Delete Parameter").clicked() {
+                parameters.pop();
+                changed = true;
+            }
+        });
+        egui::Grid::new("parameter_grid")
+            .spacing([8.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for (parameter, error) in parameters.iter_mut().zip(errors) {
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut parameter.name)
+                            .desired_width(60.0)
+                            .hint_text("Name"),
+                    ).on_hover_text("Use it in any expression, e.g. base * (1 + g1)^t")
+                        .changed();
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut parameter.value)
+                            .desired_width(60.0)
+                            .hint_text("Value"),
+                    ).changed();
+                    ui.add(
+                        egui::TextEdit::singleline(&mut parameter.description)
+                            .desired_width(120.0)
+                            .hint_text("Description"),
+                    );
+                    if let Some(err) = error {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {err}"));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if changed {
+            self.cache = None;
+        }
+    }
+}
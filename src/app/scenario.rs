@@ -0,0 +1,61 @@
+use eframe::egui;
+
+use super::AppState;
+
+impl AppState {
+    /// Scenario section of the left panel: pick the live scenario, rename and weight them.
+    pub(super) fn scenario_ui(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("Add Scenario")
+                .on_hover_text("Copies the current rows, discount and growth")
+                .clicked()
+            {
+                self.state.add_scenario();
+                changed = true;
+            }
+            if ui.add_enabled(!self.state.scenarios.is_empty(), egui::Button::new("Delete Scenario")).clicked() {
+                self.state.remove_scenario();
+                changed = true;
+            }
+        });
+
+        let mut switch_to = None;
+        egui::Grid::new("scenario_grid")
+            .spacing([8.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                let active = self.state.active_scenario;
+                for (i, scenario) in self.state.scenarios.iter_mut().enumerate() {
+                    if ui.radio(i == active, "").clicked() && i != active {
+                        switch_to = Some(i);
+                    }
+                    changed |= ui.add(
+                        egui::TextEdit::singleline(&mut scenario.name)
+                            .desired_width(100.0)
+                            .hint_text("Name"),
+                    ).changed();
+                    if ui.add(
+                        egui::TextEdit::singleline(&mut scenario.probability)
+                            .desired_width(50.0)
+                            .hint_text("Weight"),
+                    ).on_hover_text("Probability weight; weights are normalized")
+                        .changed()
+                    {
+                        let mut dot_counter: usize = 0;
+                        scenario.probability.retain(|c| super::retain_float(c, &mut dot_counter));
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some(i) = switch_to {
+            self.state.switch_scenario(i);
+            changed = true;
+        }
+        if changed {
+            self.cache = None;
+        }
+    }
+}
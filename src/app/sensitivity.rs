@@ -0,0 +1,152 @@
+use eframe::egui::{self, Color32};
+use futures::channel::oneshot;
+
+use crate::engine::{Assumption, SensitivityAxis};
+
+#[cfg(not(target_arch = "wasm32"))]
+use rfd::FileDialog;
+#[cfg(target_arch = "wasm32")]
+use rfd::AsyncFileDialog;
+
+use super::{float_field, AppState};
+
+/// Red for the lowest result through yellow to green for the highest.
+fn heat_color(value: f64, lo: f64, hi: f64) -> Color32 {
+    let w = if hi > lo { ((value - lo) / (hi - lo)) as f32 } else { 0.5 };
+    let red = Color32::from_rgb(230, 124, 115);
+    let yellow = Color32::from_rgb(255, 214, 102);
+    let green = Color32::from_rgb(87, 187, 138);
+    if w < 0.5 {
+        red.lerp_to_gamma(yellow, w * 2.0)
+    } else {
+        yellow.lerp_to_gamma(green, w * 2.0 - 1.0)
+    }
+}
+
+/// Assumption picker and range of one axis; returns `true` when it changed.
+fn axis_ui(ui: &mut egui::Ui, label: &str, assumptions: &[Assumption], axis: &mut SensitivityAxis) -> bool {
+    let width = ui.available_width();
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        egui::ComboBox::from_id_salt(("sensitivity_axis", label))
+            .selected_text(axis.assumption.label())
+            .show_ui(ui, |ui| {
+                for assumption in assumptions {
+                    changed |= ui.selectable_value(&mut axis.assumption, assumption.clone(), assumption.label()).changed();
+                }
+            });
+    });
+    changed |= float_field(ui, width, "From: ", &mut axis.from);
+    changed |= float_field(ui, width, "To: ", &mut axis.to);
+    changed |= ui.horizontal(|ui| {
+        ui.label("Steps: ");
+        let changed = ui.text_edit_singleline(&mut axis.steps)
+            .on_hover_text(format!("At most {}", SensitivityAxis::MAX_STEPS))
+            .changed();
+        if changed {
+            axis.steps.retain(|c| c.is_ascii_digit());
+        }
+        changed
+    }).inner;
+    changed
+}
+
+impl AppState {
+    fn save_sensitivity_csv(&mut self, csv: String) {
+
+        let (tx, rx) = oneshot::channel::<(String, String)>();
+        self.pending_popup = Some(rx);
+
+        #[cfg(not(target_arch = "wasm32"))] {
+            if let Some(path) = FileDialog::new()
+                .add_filter("csv", &["csv"])
+                .set_file_name("sensitivity.csv")
+                .save_file()
+            {
+                let _ = match std::fs::write(path, csv) {
+                    Ok(_) => tx.send(("Successfully Exported".into(), "Successfully exported without any error".into())),
+                    Err(e) => tx.send(("Error Occurred".into(), format!("Error while exporting: {e}"))),
+                };
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")] {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(handle) = AsyncFileDialog::new()
+                    .set_file_name("sensitivity.csv")
+                    .save_file()
+                    .await
+                {
+                    let _ = match handle.write(csv.as_bytes()).await {
+                        Ok(_) => tx.send(("Successfully Exported".into(), "Successfully exported without any error".into())),
+                        Err(e) => tx.send(("Error Occurred".into(), format!("Error while exporting: {e}"))),
+                    };
+                }
+            });
+        }
+    }
+
+    /// Two-way sensitivity window: axis settings and a color-coded grid of DCF results.
+    pub(super) fn sensitivity_ui(&mut self, ui: &mut egui::Ui) {
+        let assumptions = self.state.assumptions();
+        let inputs = &mut self.state.sensitivity;
+        let mut changed = false;
+        ui.columns(2, |columns| {
+            changed |= axis_ui(&mut columns[0], "Rows: ", &assumptions, &mut inputs.rows);
+            changed |= axis_ui(&mut columns[1], "Columns: ", &assumptions, &mut inputs.columns);
+        });
+        if changed {
+            self.sensitivity = None;
+        }
+        // every cell is a full valuation, so the grid is only built on request
+        if ui.button("Compute").clicked() {
+            self.sensitivity = Some(self.state.sensitivity_table());
+        }
+
+        ui.separator();
+        let Some(table) = self.sensitivity.clone() else {
+            ui.label("Press Compute to build the table for the current scenario.");
+            return;
+        };
+        if table.row_values.is_empty() || table.column_values.is_empty() {
+            ui.colored_label(ui.visuals().error_fg_color, "⚠ Fill both ranges with numbers");
+            return;
+        }
+
+        let range = table.range();
+        egui::Grid::new("sensitivity_grid")
+            .spacing([4.0, 4.0])
+            .show(ui, |ui| {
+                ui.strong(format!("{} \\ {}", table.rows.label(), table.columns.label()));
+                for v in &table.column_values {
+                    ui.strong(format!("{v:.4}"));
+                }
+                ui.end_row();
+
+                for (v, results) in table.row_values.iter().zip(&table.results) {
+                    ui.strong(format!("{v:.4}"));
+                    for result in results {
+                        match (result, range) {
+                            (Some(result), Some((lo, hi))) => {
+                                egui::Frame::new()
+                                    .fill(heat_color(*result, lo, hi))
+                                    .inner_margin(2.0)
+                                    .show(ui, |ui| {
+                                        ui.colored_label(Color32::BLACK, format!("{result:.2}"));
+                                    });
+                            },
+                            _ => {
+                                ui.colored_label(ui.visuals().error_fg_color, "invalid");
+                            },
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if ui.button("Export CSV").clicked() {
+            self.save_sensitivity_csv(table.to_csv());
+        }
+    }
+}
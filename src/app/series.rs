@@ -0,0 +1,75 @@
+use eframe::egui;
+
+use crate::engine::{Row, Series};
+
+use super::{rows_grid, AppState};
+
+impl AppState {
+    /// Named series section of the left panel: piecewise rows per series and the one discounted.
+    pub(super) fn series_ui(&mut self, ui: &mut egui::Ui) {
+        let (evaluated, errors) = self.cache.as_ref()
+            .map(|v| (v.series.clone(), v.series_errors.clone()))
+            .unwrap_or_default();
+        let state = &mut self.state;
+        let mut changed = false;
+
+        let selected = if state.discounted_series.is_empty() { "Rows" } else { state.discounted_series.as_str() };
+        egui::ComboBox::from_label("Discounted Series")
+            .selected_text(selected.to_owned())
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut state.discounted_series, String::new(), "Rows").changed();
+                for series in &state.series {
+                    changed |= ui.selectable_value(&mut state.discounted_series, series.name.clone(), &series.name).changed();
+                }
+            });
+        ui.horizontal(|ui| {
+            if ui.button("Add Series").on_hover_text("Refer to it from any expression as name(T)").clicked() {
+                state.series.push(Series::default());
+                changed = true;
+            }
+            if ui.add_enabled(!state.series.is_empty(), egui::Button::new("Delete Series")).clicked() {
+                state.series.pop();
+                changed = true;
+            }
+        });
+
+        for (k, series) in state.series.iter_mut().enumerate() {
+            let row_errors = evaluated.get(k).map(|s| s.row_errors.as_slice()).unwrap_or_default();
+            let title = if series.name.is_empty() { format!("Series {}", k + 1) } else { series.name.clone() };
+            egui::CollapsingHeader::new(title).id_salt(("series", k)).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let old_name = series.name.clone();
+                    if ui.add(
+                        egui::TextEdit::singleline(&mut series.name)
+                            .desired_width(100.0)
+                            .hint_text("Name"),
+                    ).changed() {
+                        // keep discounting the renamed series
+                        if state.discounted_series == old_name {
+                            state.discounted_series = series.name.clone();
+                        }
+                        changed = true;
+                    }
+                    if ui.button("Add").clicked() {
+                        series.rows.push(Row::default());
+                        changed = true;
+                    }
+                    if ui.button("Delete").clicked() {
+                        series.rows.pop();
+                        changed = true;
+                    }
+                });
+
+                changed |= rows_grid(ui, ("series_grid", k), &mut series.rows, row_errors, true).inner;
+            });
+        }
+
+        for err in errors {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {err}"));
+        }
+
+        if changed {
+            self.cache = None;
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use eframe::egui::{self, Color32};
+use egui_plot::{Bar, BarChart, GridMark, Legend, Plot, VLine};
+
+use crate::engine::TornadoBar;
+
+use super::{float_field, AppState};
+
+impl AppState {
+    /// Tornado panel: flex setting and one bar per input, the largest impact on top.
+    pub(super) fn tornado_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Tornado");
+            if float_field(ui, 200.0, "Flex (%): ", &mut self.state.tornado_flex) {
+                self.tornado = None;
+            }
+            // two full valuations per input, so the bars are only built on request
+            if ui.button("Compute").clicked() {
+                self.tornado = Some(self.state.tornado());
+            }
+        });
+        let Some(tornado) = self.tornado.clone() else {
+            ui.label("Press Compute to flex the inputs of the current scenario.");
+            return;
+        };
+
+        let Some(base) = tornado.base else {
+            ui.colored_label(ui.visuals().error_fg_color, "⚠ The base DCF result is invalid");
+            return;
+        };
+        if tornado.bars.is_empty() {
+            ui.label("No inputs to flex");
+            return;
+        }
+
+        let flex = self.state.tornado_flex.clone();
+        let n = tornado.bars.len();
+        // largest impact at the top
+        let position = |i: usize| (n - 1 - i) as f64;
+        let bars = |pick: fn(&TornadoBar) -> Option<f64>| -> Vec<Bar> {
+            tornado.bars.iter()
+                .enumerate()
+                .filter_map(|(i, bar)| {
+                    let value = pick(bar)?;
+                    Some(Bar::new(position(i), value - base).base_offset(base).name(&bar.label))
+                })
+                .collect()
+        };
+        let down = BarChart::new(format!("-{flex}%"), bars(|b| b.low))
+            .horizontal()
+            .color(Color32::from_rgb(230, 124, 115));
+        let up = BarChart::new(format!("+{flex}%"), bars(|b| b.high))
+            .horizontal()
+            .color(Color32::from_rgb(87, 187, 138));
+
+        let labels: Vec<String> = tornado.bars.iter().map(|b| b.label.clone()).collect();
+        Plot::new("tornado_plot")
+            .legend(Legend::default())
+            .allow_scroll(false)
+            .y_grid_spacer(move |_| {
+                (0..n).map(|i| GridMark { value: i as f64, step_size: 1.0 }).collect()
+            })
+            .y_axis_formatter(move |mark, _| {
+                let i = mark.value.round();
+                if (mark.value - i).abs() > 1e-6 || i < 0.0 || i as usize >= n {
+                    return String::new();
+                }
+                labels[n - 1 - i as usize].clone()
+            })
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(down);
+                plot_ui.bar_chart(up);
+                plot_ui.vline(VLine::new("Base", base).color(Color32::GRAY));
+            });
+    }
+}
@@ -0,0 +1,46 @@
+use eframe::egui;
+
+use super::{float_field, signed_float_field, AppState};
+
+impl AppState {
+    /// WACC builder section of the left panel.
+    pub(super) fn wacc_ui(&mut self, ui: &mut egui::Ui) {
+        let width = ui.available_width();
+        let wacc = &mut self.state.wacc;
+        let mut changed = ui.checkbox(&mut wacc.enabled, "Use as discount rate").changed();
+
+        changed |= signed_float_field(ui, width, "Risk-Free Rate: ", &mut wacc.risk_free_rate);
+        changed |= signed_float_field(ui, width, "Beta: ", &mut wacc.beta);
+        changed |= float_field(ui, width, "Equity Risk Premium: ", &mut wacc.equity_risk_premium);
+        changed |= float_field(ui, width, "Pre-Tax Cost of Debt: ", &mut wacc.cost_of_debt);
+        changed |= float_field(ui, width, "Tax Rate: ", &mut wacc.tax_rate);
+        changed |= float_field(ui, width, "Equity Weight: ", &mut wacc.equity_weight);
+        changed |= float_field(ui, width, "Debt Weight: ", &mut wacc.debt_weight);
+
+        match wacc.build() {
+            Some(w) => {
+                egui::Grid::new("wacc_result").show(ui, |ui| {
+                    ui.label("Cost of Equity (CAPM)");
+                    ui.label(format!("{:.4}", w.cost_of_equity));
+                    ui.end_row();
+                    ui.label("After-Tax Cost of Debt");
+                    ui.label(format!("{:.4}", w.after_tax_cost_of_debt));
+                    ui.end_row();
+                    ui.label("Weights (E / D)");
+                    ui.label(format!("{:.3} / {:.3}", w.equity_weight, w.debt_weight));
+                    ui.end_row();
+                    ui.strong("WACC");
+                    ui.strong(format!("{:.4}", w.wacc));
+                    ui.end_row();
+                });
+            },
+            None => {
+                ui.colored_label(ui.visuals().error_fg_color, "⚠ Fill every input with a number");
+            },
+        }
+
+        if changed && self.state.apply_wacc() {
+            self.cache = None;
+        }
+    }
+}
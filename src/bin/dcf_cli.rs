@@ -0,0 +1,265 @@
+//! Command-line front-end: values saved scenario files without opening the GUI.
+
+use std::io::Read as _;
+use std::process::ExitCode;
+
+use dcf_simulator::engine::{Driver, ExpectedValuation, Frequency, GoalSeekError, Root, StateData, Valuation};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: dcf_cli [--format table|json|csv] [FILE...]
+
+Values one or more scenario JSON files saved by the DCF simulator.
+Reads stdin when no FILE (or `-`) is given.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct Report {
+    file: String,
+    frequency: Frequency,
+    #[serde(flatten)]
+    valuation: Valuation,
+    /// Probability-weighted result, for files with scenarios.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<ExpectedValuation>,
+    /// Discount rate implied by the market value, for files that give one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implied_discount: Option<Result<Root, GoalSeekError>>,
+    /// Terminal growth implied by the market value, for files that give one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implied_growth: Option<Result<Root, GoalSeekError>>,
+    /// Multiplier of the goal seek row implied by the market value, for files that pick a row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implied_multiplier: Option<Result<Root, GoalSeekError>>,
+}
+
+fn read_scenario(path: &str) -> Result<StateData, String> {
+    let data = if path == "-" {
+        let mut buf = Vec::new();
+        std::io::stdin().read_to_end(&mut buf).map_err(|e| format!("Error while reading stdin: {e}"))?;
+        buf
+    } else {
+        std::fs::read(path).map_err(|e| format!("Error while loading {path}: {e}"))?
+    };
+    serde_json::from_slice::<StateData>(&data).map_err(|e| format!("Error while loading {path}: {e}"))
+}
+
+fn print_table(report: &Report) {
+    println!("== {} ==", report.file);
+    let dates = report.valuation.dates.as_ref();
+    let t_header = match (dates, report.frequency) {
+        (Some(_), _) => "Date",
+        (None, Frequency::Annual) => "t",
+        (None, frequency) => frequency.period_label(),
+    };
+    // run-rate per year of sub-annual cash-flows
+    let annualized = report.frequency != Frequency::Annual;
+    let periods_per_year = report.frequency.periods_per_year() as f64;
+    print!("{t_header:>10} {:>20}", "Cashflow");
+    if annualized {
+        print!(" {:>20}", "Annualized");
+    }
+    println!(" {:>20} {:>20}", "UNIT DCF", "Sum of DCF");
+    for (t, d) in report.valuation.dcf.iter().enumerate() {
+        let t = dates.and_then(|dates| dates.get(t)).map(|d| d.to_string()).unwrap_or(t.to_string());
+        print!("{t:>10} {:>20.6}", d.cashflow);
+        if annualized {
+            print!(" {:>20.6}", d.cashflow * periods_per_year);
+        }
+        println!(" {:>20.6} {:>20.6}", d.dcf_unit, d.dcf_sum);
+    }
+    match &report.valuation.terminal_value {
+        Ok(tv) => println!("Terminal Value: {} (PV: {})", tv.value, tv.pv),
+        Err(e) => println!("Terminal Value: invalid ({e})"),
+    }
+    match report.valuation.total {
+        Some(total) => println!("DCF Result: {total}"),
+        None => println!("DCF Result: invalid"),
+    }
+    if let Some(expected) = &report.expected {
+        for s in &expected.scenarios {
+            let total = s.total.map(|v| v.to_string()).unwrap_or("invalid".into());
+            println!("Scenario {} ({:.1}%): {total}", s.name, s.probability * 100.0);
+        }
+        match expected.expected {
+            Some(value) => println!("Expected DCF Result: {value}"),
+            None => println!("Expected DCF Result: invalid"),
+        }
+    }
+    let implied = [
+        ("Discount Rate", &report.implied_discount),
+        ("Growth", &report.implied_growth),
+        ("Multiplier", &report.implied_multiplier),
+    ];
+    for (label, implied) in implied {
+        match implied {
+            Some(Ok(root)) => println!("Implied {label}: {}", root.value),
+            Some(Err(e)) => println!("Implied {label}: {e}"),
+            None => {},
+        }
+    }
+    if let Some(equity) = &report.valuation.equity {
+        println!("Equity Value: {}", equity.equity_value);
+        if let Some(per_share) = equity.per_share {
+            println!("Per Share: {per_share}");
+        }
+        if let Some(upside) = equity.upside {
+            println!("Upside: {:.2}%", upside * 100.0);
+        }
+    }
+}
+
+fn print_csv(reports: &[Report]) {
+    println!(
+        "file,t,date,cashflow,annualized_cashflow,dcf_unit,dcf_sum,terminal_value,terminal_value_pv,dcf_result,equity_value,per_share"
+    );
+    for report in reports {
+        // Quote the file name only when it would break the row.
+        let file = if report.file.contains([',', '"', '\n']) {
+            format!("\"{}\"", report.file.replace('"', "\"\""))
+        } else {
+            report.file.clone()
+        };
+        // invalid terminal values are left empty
+        let (tv, tv_pv) = match &report.valuation.terminal_value {
+            Ok(tv) => (tv.value.to_string(), tv.pv.to_string()),
+            Err(_) => (String::new(), String::new()),
+        };
+        let total = report.valuation.total.map(|v| v.to_string()).unwrap_or_default();
+        let equity = report.valuation.equity.map(|e| e.equity_value.to_string()).unwrap_or_default();
+        let per_share = report.valuation.equity.and_then(|e| e.per_share).map(|v| v.to_string()).unwrap_or_default();
+        let periods_per_year = report.frequency.periods_per_year() as f64;
+        for (t, d) in report.valuation.dcf.iter().enumerate() {
+            let date = report.valuation.dates.as_ref().and_then(|dates| dates.get(t)).map(|d| d.to_string()).unwrap_or_default();
+            let annualized = d.cashflow * periods_per_year;
+            println!(
+                "{file},{t},{date},{},{annualized},{},{},{tv},{tv_pv},{total},{equity},{per_share}",
+                d.cashflow, d.dcf_unit, d.dcf_sum,
+            );
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut format = Format::Table;
+    let mut files: Vec<String> = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            },
+            "-f" | "--format" => {
+                format = match args.next().as_deref() {
+                    Some("table") => Format::Table,
+                    Some("json") => Format::Json,
+                    Some("csv") => Format::Csv,
+                    other => {
+                        eprintln!("Unknown format: {}\n\n{USAGE}", other.unwrap_or(""));
+                        return ExitCode::FAILURE;
+                    },
+                };
+            },
+            option if option.starts_with('-') && option != "-" => {
+                eprintln!("Unknown option: {option}\n\n{USAGE}");
+                return ExitCode::FAILURE;
+            },
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        files.push("-".into());
+    }
+
+    let mut failed = false;
+    let mut reports = Vec::new();
+    for file in files {
+        let state = match read_scenario(&file) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                continue;
+            },
+        };
+        let valuation = state.evaluate();
+        for (i, err) in state.parameter_errors().iter().enumerate() {
+            if let Some(err) = err {
+                eprintln!("{file}: parameter {}: {err}", i + 1);
+                failed = true;
+            }
+        }
+        for (i, err) in valuation.row_errors.iter().enumerate() {
+            if let Some(err) = err {
+                eprintln!("{file}: row {}: {err}", i + 1);
+                failed = true;
+            }
+        }
+        if let Some(drivers) = &valuation.drivers {
+            for driver in Driver::ALL {
+                for (i, err) in drivers.row_errors(driver).iter().enumerate() {
+                    if let Some(err) = err {
+                        eprintln!("{file}: {} row {}: {err}", driver.label(), i + 1);
+                        failed = true;
+                    }
+                }
+            }
+        }
+        for series in &valuation.series {
+            for (i, err) in series.row_errors.iter().enumerate() {
+                if let Some(err) = err {
+                    eprintln!("{file}: series {} row {}: {err}", series.name, i + 1);
+                    failed = true;
+                }
+            }
+        }
+        for e in &valuation.series_errors {
+            eprintln!("{file}: {e}");
+            failed = true;
+        }
+        if let Some(e) = &valuation.curve_error {
+            eprintln!("{file}: {e}");
+            failed = true;
+        }
+        if let Some(e) = &valuation.calendar_error {
+            eprintln!("{file}: {e}");
+            failed = true;
+        }
+        if let Err(e) = &valuation.terminal_value {
+            eprintln!("{file}: {e}");
+            failed = true;
+        }
+        let expected = (!state.scenarios.is_empty()).then(|| state.expected_valuation());
+        let implied_discount = state.target_value().map(|_| state.implied_discount());
+        let implied_growth = state.target_value().map(|_| state.implied_growth());
+        let implied_multiplier = state.target_value()
+            .filter(|_| !state.goal_seek.row.is_empty())
+            .map(|_| state.implied_multiplier());
+        reports.push(Report {
+            file,
+            frequency: state.frequency,
+            valuation,
+            expected,
+            implied_discount,
+            implied_growth,
+            implied_multiplier,
+        });
+    }
+
+    match format {
+        Format::Table => reports.iter().for_each(print_table),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&reports).unwrap()),
+        Format::Csv => print_csv(&reports),
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
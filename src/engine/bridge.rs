@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use super::StateData;
+
+/// Inputs of the enterprise-to-equity bridge. Amounts are in the units of the cash-flows.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgeInputs {
+    /// Debt less cash
+    pub net_debt: String,
+    pub minority_interest: String,
+    pub preferred_equity: String,
+    /// Cash-generating assets not in the cash-flows (investments, surplus real estate, ...)
+    pub non_operating_assets: String,
+    pub diluted_shares: String,
+    /// Current market price per share to compare against.
+    pub market_price: String,
+}
+
+/// Equity value derived from the DCF result.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct EquityValue {
+    pub enterprise_value: f64,
+    /// `enterprise_value - net_debt - minority_interest - preferred_equity + non_operating_assets`
+    pub equity_value: f64,
+    /// `None` without a positive share count.
+    pub per_share: Option<f64>,
+    /// `per_share / market_price - 1`; `None` without both.
+    pub upside: Option<f64>,
+    /// `1 - market_price / per_share`; `None` without both.
+    pub margin_of_safety: Option<f64>,
+}
+
+impl BridgeInputs {
+    /// Bridges `enterprise_value` to equity; empty or invalid amounts count as zero.
+    pub fn apply(&self, enterprise_value: f64) -> EquityValue {
+        let amount = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+        let equity_value = enterprise_value
+            - amount(&self.net_debt)
+            - amount(&self.minority_interest)
+            - amount(&self.preferred_equity)
+            + amount(&self.non_operating_assets);
+
+        let per_share = match self.diluted_shares.parse::<f64>() {
+            Ok(shares) if shares > 0.0 => Some(equity_value / shares),
+            _ => None,
+        };
+        let price = self.market_price.parse::<f64>().ok().filter(|&p| p > 0.0);
+        let (upside, margin_of_safety) = match (per_share, price) {
+            (Some(value), Some(price)) => (Some(value / price - 1.0), Some(1.0 - price / value)),
+            _ => (None, None),
+        };
+
+        EquityValue { enterprise_value, equity_value, per_share, upside, margin_of_safety }
+    }
+
+    /// Enterprise value the market implies: market capitalization bridged back the other way.
+    /// `None` without a market price and share count.
+    pub fn market_enterprise_value(&self) -> Option<f64> {
+        let amount = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+        let price: f64 = self.market_price.parse().ok()?;
+        let shares: f64 = self.diluted_shares.parse().ok()?;
+        Some(
+            price * shares
+                + amount(&self.net_debt)
+                + amount(&self.minority_interest)
+                + amount(&self.preferred_equity)
+                - amount(&self.non_operating_assets),
+        )
+    }
+}
+
+impl StateData {
+    /// Equity bridge applied to a DCF result.
+    pub fn equity_value(&self, enterprise_value: f64) -> EquityValue {
+        self.bridge.apply(enterprise_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::assert_close;
+
+    fn inputs(net_debt: &str, shares: &str, price: &str) -> BridgeInputs {
+        BridgeInputs {
+            net_debt: net_debt.into(),
+            minority_interest: "50".into(),
+            preferred_equity: "30".into(),
+            non_operating_assets: "80".into(),
+            diluted_shares: shares.into(),
+            market_price: price.into(),
+        }
+    }
+
+    #[test]
+    fn apply() {
+        let equity = inputs("200", "10", "64").apply(1000.0);
+        assert_close(equity.enterprise_value, 1000.0);
+        assert_close(equity.equity_value, 800.0);
+        assert_close(equity.per_share.unwrap(), 80.0);
+        assert_close(equity.upside.unwrap(), 0.25);
+        assert_close(equity.margin_of_safety.unwrap(), 0.2);
+    }
+
+    #[test]
+    fn net_cash_adds_to_equity() {
+        assert_close(inputs("-200", "", "").apply(1000.0).equity_value, 1200.0);
+    }
+
+    #[test]
+    fn per_share_needs_a_positive_share_count() {
+        for shares in ["", "0", "-5"] {
+            let equity = inputs("200", shares, "64").apply(1000.0);
+            assert_eq!(equity.per_share, None);
+            assert_eq!(equity.upside, None);
+        }
+        let equity = inputs("200", "10", "").apply(1000.0);
+        assert_eq!((equity.upside, equity.margin_of_safety), (None, None));
+    }
+
+    #[test]
+    fn market_enterprise_value_bridges_back() {
+        assert_close(inputs("200", "10", "64").market_enterprise_value().unwrap(), 840.0);
+        assert_eq!(inputs("200", "", "64").market_enterprise_value(), None);
+    }
+}
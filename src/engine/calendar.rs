@@ -0,0 +1,343 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::StateData;
+
+/// Length of one period. Rates are entered per year and converted to the period length.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    #[default]
+    Annual,
+    SemiAnnual,
+    Quarterly,
+    Monthly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 4] = [Frequency::Annual, Frequency::SemiAnnual, Frequency::Quarterly, Frequency::Monthly];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Frequency::Annual => "Annual",
+            Frequency::SemiAnnual => "Semi-Annual",
+            Frequency::Quarterly => "Quarterly",
+            Frequency::Monthly => "Monthly",
+        }
+    }
+
+    pub fn months(&self) -> u32 {
+        match self {
+            Frequency::Annual => 12,
+            Frequency::SemiAnnual => 6,
+            Frequency::Quarterly => 3,
+            Frequency::Monthly => 1,
+        }
+    }
+
+    /// Length of a period in years.
+    pub fn years(&self) -> f64 {
+        self.months() as f64 / 12.0
+    }
+
+    pub fn periods_per_year(&self) -> u32 {
+        12 / self.months()
+    }
+
+    /// Name of one period, for table headers.
+    pub fn period_label(&self) -> &'static str {
+        match self {
+            Frequency::Annual => "Year",
+            Frequency::SemiAnnual => "Half-Year",
+            Frequency::Quarterly => "Quarter",
+            Frequency::Monthly => "Month",
+        }
+    }
+}
+
+/// Calendar date in the proleptic Gregorian calendar, written `YYYY-MM-DD`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl Date {
+    pub fn parse(s: &str) -> Option<Date> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day))
+            .then_some(Date { year, month, day })
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(&self) -> i64 {
+        // Howard Hinnant's days_from_civil
+        let y = if self.month <= 2 { self.year - 1 } else { self.year } as i64;
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    /// `months` later, the day clamped to the end of the month (Jan 31 + 1 month = Feb 28/29).
+    pub fn add_months(&self, months: u32) -> Date {
+        let total = self.year as i64 * 12 + (self.month - 1) as i64 + months as i64;
+        let year = total.div_euclid(12) as i32;
+        let month = total.rem_euclid(12) as u32 + 1;
+        Date { year, month, day: self.day.min(days_in_month(year, month)) }
+    }
+
+    /// Actual/365 year fraction from `self` to `to`, as used by XNPV.
+    pub fn year_fraction(&self, to: &Date) -> f64 {
+        (to.days() - self.days()) as f64 / 365.0
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Calendar dates of the periods. When `enabled`, every cash-flow is discounted over its exact
+/// year fraction from the valuation date.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarInputs {
+    pub enabled: bool,
+    /// Date of t = 0, `YYYY-MM-DD`
+    pub valuation_date: String,
+    /// Explicit dates of periods 1, 2, ...; periods past the list follow the [`Frequency`] from
+    /// the last one. Empty means every period follows the frequency.
+    pub dates: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalendarError {
+    InvalidValuationDate { date: String },
+    /// Explicit date of period `period` does not parse.
+    InvalidDate { period: usize, date: String },
+    /// Explicit date of period `period` is not after the date before it.
+    NonIncreasingDate { period: usize, date: String },
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalendarError::InvalidValuationDate { date } => {
+                write!(f, "Invalid valuation date `{date}`, expected YYYY-MM-DD")
+            },
+            CalendarError::InvalidDate { period, date } => {
+                write!(f, "Invalid date `{date}` of period {period}, expected YYYY-MM-DD")
+            },
+            CalendarError::NonIncreasingDate { period, date } => {
+                write!(f, "Date `{date}` of period {period} is not after the previous date")
+            },
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
+impl StateData {
+    /// Dates of periods `0..len`; `Ok(None)` when the calendar is off.
+    pub fn period_dates(&self, len: usize) -> Result<Option<Vec<Date>>, CalendarError> {
+        let calendar = &self.calendar;
+        if !calendar.enabled {
+            return Ok(None);
+        }
+        let start = Date::parse(&calendar.valuation_date)
+            .ok_or_else(|| CalendarError::InvalidValuationDate { date: calendar.valuation_date.clone() })?;
+
+        let mut dates = vec![start];
+        for (i, date) in calendar.dates.iter().enumerate().take(len.saturating_sub(1)) {
+            let period = i + 1;
+            let parsed = Date::parse(date)
+                .ok_or_else(|| CalendarError::InvalidDate { period, date: date.clone() })?;
+            if parsed <= *dates.last().unwrap() {
+                return Err(CalendarError::NonIncreasingDate { period, date: date.clone() });
+            }
+            dates.push(parsed);
+        }
+        // continue from the last date so the frequency never drifts
+        let anchor = *dates.last().unwrap();
+        for k in 1..=len.saturating_sub(dates.len()) {
+            dates.push(anchor.add_months(k as u32 * self.frequency.months()));
+        }
+        Ok(Some(dates))
+    }
+
+    /// Year fraction of each period boundary `0..len`; `None` for plain annual periods, where
+    /// the period index is the time already.
+    ///
+    /// Without the calendar, or while its dates are invalid, the periods are spaced evenly by
+    /// the [`Frequency`].
+    pub fn period_times(&self, len: usize) -> Option<Vec<f64>> {
+        match self.period_dates(len) {
+            Ok(None) if self.frequency == Frequency::Annual => None,
+            Ok(Some(dates)) => Some(dates.iter().map(|d| dates[0].year_fraction(d)).collect()),
+            Ok(None) | Err(_) => Some((0..len).map(|k| k as f64 * self.frequency.years()).collect()),
+        }
+    }
+
+    /// Length of one period in years, the unit rates are quoted in.
+    pub fn period_years(&self) -> f64 {
+        self.frequency.years()
+    }
+
+    /// Gross rate per year as the compounding-equivalent gross rate per period.
+    pub fn per_period(&self, gross: f64) -> f64 {
+        gross.powf(self.period_years())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row};
+
+    fn date(s: &str) -> Date {
+        Date::parse(s).unwrap()
+    }
+
+    fn calendar(valuation_date: &str, dates: &[&str]) -> StateData {
+        StateData {
+            rows: vec![row("3", "100")],
+            calendar: CalendarInputs {
+                enabled: true,
+                valuation_date: valuation_date.into(),
+                dates: dates.iter().map(|d| d.to_string()).collect(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_checks_the_day_of_the_month() {
+        assert_eq!(date("2024-02-29"), Date { year: 2024, month: 2, day: 29 });
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2024-13-01"), None);
+        assert_eq!(Date::parse("2024-01"), None);
+        assert_eq!(date(" 2024-01-05 ").to_string(), "2024-01-05");
+    }
+
+    #[test]
+    fn add_months_clamps_to_the_end_of_the_month() {
+        assert_eq!(date("2024-01-31").add_months(1), date("2024-02-29"));
+        assert_eq!(date("2023-01-31").add_months(1), date("2023-02-28"));
+        assert_eq!(date("2024-11-15").add_months(3), date("2025-02-15"));
+    }
+
+    #[test]
+    fn year_fraction_is_actual_over_365() {
+        assert_eq!(date("1970-01-01").days(), 0);
+        assert_close(date("2024-01-01").year_fraction(&date("2025-01-01")), 366.0 / 365.0);
+        assert_close(date("2023-01-01").year_fraction(&date("2023-07-02")), 182.0 / 365.0);
+    }
+
+    #[test]
+    fn explicit_dates_then_the_frequency() {
+        let mut state = calendar("2024-01-31", &["2024-03-31"]);
+        state.frequency = Frequency::Quarterly;
+        let dates = state.period_dates(4).unwrap().unwrap();
+        let dates: Vec<String> = dates.iter().map(Date::to_string).collect();
+        assert_eq!(dates, ["2024-01-31", "2024-03-31", "2024-06-30", "2024-09-30"]);
+        assert_eq!(StateData::default().period_dates(4), Ok(None));
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(
+            calendar("31.01.2024", &[]).period_dates(2),
+            Err(CalendarError::InvalidValuationDate { date: "31.01.2024".into() })
+        );
+        assert_eq!(
+            calendar("2024-01-31", &["2024-06-30", "soon"]).period_dates(4),
+            Err(CalendarError::InvalidDate { period: 2, date: "soon".into() })
+        );
+        assert_eq!(
+            calendar("2024-01-31", &["2024-01-31"]).period_dates(2),
+            Err(CalendarError::NonIncreasingDate { period: 1, date: "2024-01-31".into() })
+        );
+        // dates past the periods valued are not checked
+        assert!(calendar("2024-01-31", &["2024-06-30", "soon"]).period_dates(2).is_ok());
+    }
+
+    #[test]
+    fn invalid_dates_space_the_periods_evenly() {
+        let mut state = calendar("", &[]);
+        state.frequency = Frequency::SemiAnnual;
+        assert_eq!(state.period_times(3), Some(vec![0.0, 0.5, 1.0]));
+        let valuation = state.evaluate();
+        assert_eq!(valuation.dates, None);
+        assert!(valuation.calendar_error.is_some());
+    }
+
+    #[test]
+    fn cashflows_are_discounted_over_year_fractions() {
+        // half a year to the first date, then a full (leap) year
+        let state = calendar("2023-07-01", &["2023-12-30", "2024-12-30", "2025-12-30"]);
+        let valuation = state.evaluate();
+        assert_eq!(valuation.calendar_error, None);
+        assert_eq!(valuation.dates.as_ref().unwrap()[1], date("2023-12-30"));
+        assert_close(valuation.dcf[1].dcf_unit, 100.0 / 1.03f64.powf(182.0 / 365.0));
+        assert_close(valuation.dcf[2].dcf_unit, 100.0 / 1.03f64.powf(548.0 / 365.0));
+    }
+
+    #[test]
+    fn rates_are_per_year() {
+        let mut state = calendar("2024-01-01", &[]);
+        state.frequency = Frequency::Quarterly;
+        assert_close(state.per_period(1.0816), 1.0816f64.powf(0.25));
+        // Gordon growth on quarterly rates
+        let tv = state.evaluate().terminal_value.unwrap();
+        let (discount, growth) = (1.03f64.powf(0.25), 1.02f64.powf(0.25));
+        assert_close(tv.value, 100.0 * growth / (discount - growth));
+        state.frequency = Frequency::Annual;
+        assert_eq!(state.per_period(1.0816), 1.0816);
+    }
+
+    #[test]
+    fn per_period_converts_annual_rates_without_the_calendar() {
+        let mut state = StateData { rows: vec![row("4", "100")], frequency: Frequency::Quarterly, ..Default::default() };
+        assert_eq!(state.period_times(3), Some(vec![0.0, 0.25, 0.5]));
+        assert_close(state.per_period(1.1), 1.1f64.powf(0.25));
+        assert_close(state.per_period(state.per_period(1.1).powi(4)), state.per_period(1.1));
+        // four quarters discount like one year
+        let valuation = state.evaluate();
+        assert_close(valuation.dcf[4].dcf_unit, 100.0 / 1.03);
+        assert_eq!(valuation.dates, None);
+
+        state.frequency = Frequency::Annual;
+        assert_eq!(state.period_times(3), None);
+        assert_close(state.evaluate().dcf[4].dcf_unit, 100.0 / 1.03f64.powi(4));
+    }
+
+    #[test]
+    fn frequency_periods() {
+        let periods: Vec<u32> = Frequency::ALL.iter().map(Frequency::periods_per_year).collect();
+        assert_eq!(periods, [1, 2, 4, 12]);
+        assert_eq!(Frequency::Monthly.period_label(), "Month");
+    }
+}
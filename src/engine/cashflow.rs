@@ -0,0 +1,359 @@
+use std::str::FromStr as _;
+
+use meval::{Context, Expr};
+use ode_solvers::{Dopri5, SVector, System};
+use serde::{Deserialize, Serialize};
+
+use super::{parameters::uses_variable, series::check_series_time, DriverSeries, NamedSeries, Row, RowError, SeriesError, StateData, ValuationMode};
+
+/// How a [`Row`]'s expression generates its values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    /// Detected from the expression's variables, see [`ModelKind::detect`].
+    #[default]
+    Auto,
+    /// The same value every period.
+    Constant,
+    /// A function of `t`, evaluated at every period.
+    TimeFunction,
+    /// A rate of change `dy/dt` in `t`, `T` and `y`, integrated from the previous segment's last value.
+    Ode,
+}
+
+impl ModelKind {
+    pub const ALL: [ModelKind; 4] = [ModelKind::Auto, ModelKind::Constant, ModelKind::TimeFunction, ModelKind::Ode];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModelKind::Auto => "Auto",
+            ModelKind::Constant => "Constant",
+            ModelKind::TimeFunction => "Time Function",
+            ModelKind::Ode => "ODE",
+        }
+    }
+
+    /// [`Ode`](ModelKind::Ode) when the expression uses `y`, [`TimeFunction`](ModelKind::TimeFunction)
+    /// when it uses `t` or `T`, [`Constant`](ModelKind::Constant) otherwise.
+    pub fn detect(expr: &Expr) -> ModelKind {
+        if uses_variable(expr, "y") {
+            ModelKind::Ode
+        } else if uses_variable(expr, "t") || uses_variable(expr, "T") {
+            ModelKind::TimeFunction
+        } else {
+            ModelKind::Constant
+        }
+    }
+
+    /// `self`, or the kind detected from `expr` for [`Auto`](ModelKind::Auto).
+    pub fn resolve(self, expr: &Expr) -> ModelKind {
+        match self {
+            ModelKind::Auto => ModelKind::detect(expr),
+            kind => kind,
+        }
+    }
+}
+
+impl Row {
+    /// Kind the expression is evaluated as, `None` while it does not parse.
+    pub fn model_kind(&self) -> Option<ModelKind> {
+        Expr::from_str(&self.expr).ok().map(|parsed| self.kind.resolve(&parsed))
+    }
+}
+
+/// What `t` counts in the expressions. `T` always counts periods from the valuation date.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBase {
+    /// `t` restarts at 0 at the beginning of every segment.
+    #[default]
+    Local,
+    /// `t` is the absolute period, the same as `T`.
+    Absolute,
+}
+
+impl TimeBase {
+    pub const ALL: [TimeBase; 2] = [TimeBase::Local, TimeBase::Absolute];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeBase::Local => "Segment-Local",
+            TimeBase::Absolute => "Absolute",
+        }
+    }
+
+    /// `(t, T)` at time `s` into a segment beginning at period `start`.
+    fn times(self, start: f64, s: f64) -> (f64, f64) {
+        match self {
+            TimeBase::Local => (s, start + s),
+            TimeBase::Absolute => (start + s, start + s),
+        }
+    }
+}
+
+/// Cash-flow series plus one diagnostic per row (`None` when the row evaluated cleanly).
+#[derive(Clone, Default, Serialize)]
+pub struct Cashflow {
+    pub values: Vec<f64>,
+    /// Gross discount rate from `t - 1` to `t`, aligned with `values`.
+    pub discount_rates: Vec<f64>,
+    pub row_errors: Vec<Option<RowError>>,
+    /// `[t, value]` points of the continuous path, sorted by `t`; only filled in
+    /// [`ValuationMode::Continuous`].
+    pub path: Vec<[f64; 2]>,
+    /// Driver columns the values were built from, in driver mode.
+    pub drivers: Option<DriverSeries>,
+    /// Every named series, aligned with [`StateData::series`].
+    pub series: Vec<NamedSeries>,
+    /// Why named series could not be evaluated or discounted.
+    pub series_errors: Vec<SeriesError>,
+}
+
+/// Output of one row.
+#[derive(Default)]
+struct Segment {
+    values: Vec<f64>,
+    /// `[t, value]` points with `t` local to the segment.
+    path: Vec<[f64; 2]>,
+}
+
+impl StateData {
+    /// Cash-flow of the scenario: the named series chosen for discounting, or else the rows
+    /// themselves, or the free cash flow built from the drivers when those are enabled.
+    pub fn calculate_cashflow(&self) -> Cashflow {
+        let (series, mut cashflows, mut series_errors) = self.calculate_named_series();
+        let scope: Vec<NamedSeries> = series.iter()
+            .zip(self.series_name_errors())
+            .zip(&cashflows)
+            .filter(|((_, err), cashflow)| err.is_none() && cashflow.is_some())
+            .map(|((s, _), _)| s.clone())
+            .collect();
+
+        let discounted = (!self.discounted_series.is_empty()).then(|| {
+            let i = self.series.iter().position(|s| s.name == self.discounted_series);
+            i.and_then(|i| cashflows[i].take())
+        });
+        let mut output = match discounted {
+            Some(Some(cashflow)) => Cashflow {
+                // shown next to the series, not next to the rows
+                row_errors: Vec::new(),
+                ..cashflow
+            },
+            Some(None) => {
+                if !series_errors.iter().any(|e| matches!(e, SeriesError::Cycle { .. })) {
+                    series_errors.push(SeriesError::UnknownSeries { name: self.discounted_series.clone() });
+                }
+                Cashflow::default()
+            },
+            None if self.drivers.enabled => self.calculate_free_cashflow(&scope),
+            None => self.calculate_series(&self.rows, &scope),
+        };
+        output.series = series;
+        output.series_errors = series_errors;
+        output
+    }
+
+    /// Evaluates every row into one value per period, starting at t = 0.
+    ///
+    /// Rows that fail keep their periods filled with `0.0` and report why in `row_errors`;
+    /// rows whose `end` is invalid or goes backwards are skipped entirely. The rows can refer to
+    /// the evaluated `series` as `name(T)`.
+    pub fn calculate_series(&self, rows: &[Row], series: &[NamedSeries]) -> Cashflow {
+        let mut output = Cashflow::default();
+        let mut prev_period: usize = 0;
+        let base_discount: f64 = self.discount.parse().unwrap_or(1.0);
+        let continuous = self.valuation_mode == ValuationMode::Continuous;
+
+        for e in rows.iter() {
+            let period = match e.end.parse::<usize>() {
+                Ok(p) => p,
+                Err(_) => {
+                    output.row_errors.push(Some(RowError::InvalidPeriod { end: e.end.clone() }));
+                    continue;
+                },
+            };
+            if period < prev_period {
+                output.row_errors.push(Some(RowError::NonMonotonicPeriod { end: period, previous: prev_period }));
+                continue;
+            }
+
+            let len = period - prev_period;
+            let ctx = self.series_context(series);
+            // `t` restarts at 0 in every segment unless it is absolute
+            let t_start = match self.time_base {
+                TimeBase::Local => prev_period,
+                TimeBase::Absolute => 0,
+            };
+            let mut error = None;
+            if output.values.is_empty() {
+                output.discount_rates.push(1.0);
+            }
+            let segment = check_series_time(&e.expr, t_start, series)
+                .and_then(|()| self.evaluate_segment(e, prev_period, len, &output.values, &ctx));
+            match segment {
+                Ok(segment) => {
+                    output.values.extend(segment.values);
+                    let start = prev_period as f64;
+                    output.path.extend(segment.path.into_iter().map(|[t, y]| [start + t, y]));
+                },
+                Err(err) => {
+                    // keep t = 0 in place if the very first segment fails
+                    let len = if output.values.is_empty() { len + 1 } else { len };
+                    output.values.extend(std::iter::repeat_n(0.0, len));
+                    if continuous {
+                        output.path.extend([[prev_period as f64, 0.0], [period as f64, 0.0]]);
+                    }
+                    error = Some(err);
+                },
+            }
+            let rates = check_series_time(&e.discount, t_start, series)
+                .and_then(|()| self.evaluate_discount(&e.discount, prev_period, len, &ctx));
+            match rates {
+                Ok(rates) => output.discount_rates.extend(rates),
+                Err(err) => {
+                    output.discount_rates.extend(std::iter::repeat_n(base_discount, len));
+                    error.get_or_insert(RowError::Discount { message: err.to_string() });
+                },
+            }
+            output.row_errors.push(error);
+            prev_period = period;
+        }
+
+        output
+    }
+
+    /// Gross discount rate of each of the `len` periods of a segment beginning at period
+    /// `start`, local time counting from 1.
+    fn evaluate_discount(&self, expr: &str, start: usize, len: usize, ctx: &Context<'static>) -> Result<Vec<f64>, RowError> {
+        if expr.trim().is_empty() {
+            return Ok(vec![self.discount.parse().unwrap_or(1.0); len]);
+        }
+
+        let parsed = Expr::from_str(expr)?;
+        if uses_variable(&parsed, "t") || uses_variable(&parsed, "T") {
+            let f = parsed.bind2_with_context(ctx, "t", "T")?;
+            Ok((1..=len)
+                .map(|s| self.time_base.times(start as f64, s as f64))
+                .map(|(t, abs)| f(t, abs))
+                .collect())
+        } else {
+            Ok(vec![parsed.eval_with_context(ctx)?; len])
+        }
+    }
+
+    /// Step size of the ODE solver and of continuous sampling.
+    pub fn step_size(&self) -> f64 {
+        match self.ode_step_size.parse::<f64>() {
+            Ok(h) if h > 0.0 => h,
+            _ => 1.0,
+        }
+    }
+
+    /// Values of `row`'s segment of `len` periods from period `start` on, following `prev`,
+    /// generated as its [`ModelKind`].
+    ///
+    /// When `prev` is empty the value at t = 0 is included as well. `ctx` supplies the
+    /// [`parameters`](StateData::parameters) besides the built-in constants. Time runs locally
+    /// from 0; `t` and `T` are derived from it by the [`TimeBase`].
+    fn evaluate_segment(
+        &self,
+        row: &Row,
+        start: usize,
+        len: usize,
+        prev: &[f64],
+        ctx: &Context<'static>,
+    ) -> Result<Segment, RowError> {
+        let parsed = Expr::from_str(&row.expr)?;
+        let continuous = self.valuation_mode == ValuationMode::Continuous;
+        let kind = row.kind.resolve(&parsed);
+        let time_base = self.time_base;
+        let start = start as f64;
+        let mut output = Segment::default();
+
+        // This part is for ODE function model
+        if kind == ModelKind::Ode {
+            let f = parsed.bind3_with_context(ctx.clone(), "t", "T", "y")?;
+            let rhs = move |s: f64, y: f64| {
+                let (t, abs) = time_base.times(start, s);
+                f(t, abs, y)
+            };
+
+            struct Sys { f: Box<dyn Fn(f64, f64)->f64> }
+            impl System<f64, SVector<f64, 1>> for Sys {
+                fn system(&self, t: f64, y: &SVector<f64, 1>, dy: &mut SVector<f64, 1>) {
+                    dy[0] = (self.f)(t, y[0]);
+                }
+            }
+
+            let y0 = prev.last().cloned().unwrap_or(0.0);
+            if len == 0 {
+                if prev.is_empty() {
+                    output.values.push(y0);
+                }
+                return Ok(output);
+            }
+
+            let mut solver = Dopri5::new(
+                Sys{f: Box::new(rhs)}, // Right-Hand Side
+                0.0, len as f64, self.step_size(), // t0, t_end, h
+                [y0].into(),          // Initial Value: y(0)
+                1e-10, 1e-10           // Error limit
+            );
+            solver.integrate().map_err(|e| RowError::Solver { message: e.to_string() })?;
+
+            let x_out = solver.x_out();
+            let y_out: Vec<f64> = solver.y_out().iter().map(|v| v[0]).collect();
+            let step = x_out.get(1).map(|x| x - x_out[0]).unwrap_or(1.0);
+            let mut n_counter: usize = if prev.is_empty() { 0 } else { 1 };
+            for (i, &x) in x_out.iter().enumerate() {
+                if x - (n_counter as f64) > -step {
+                    output.values.push(y_out[i]);
+                    n_counter += 1;
+                }
+            }
+            if continuous {
+                output.path = x_out.iter().zip(&y_out).map(|(&x, &y)| [x, y]).collect();
+            }
+
+        // This part is just for univariant function model
+        } else if kind == ModelKind::TimeFunction {
+            let g = parsed.bind2_with_context(ctx, "t", "T")?;
+            let f = |s: f64| {
+                let (t, abs) = time_base.times(start, s);
+                g(t, abs)
+            };
+
+            if prev.is_empty() {
+                output.values.push(f(0.0));
+            }
+
+            for t in 1..=len {
+                output.values.push(f(t as f64));
+            }
+
+            if continuous {
+                let steps = (len as f64 / self.step_size()).ceil() as usize;
+                output.path = (0..=steps)
+                    .map(|k| f64::min(k as f64 * self.step_size(), len as f64))
+                    .map(|t| [t, f(t)])
+                    .collect();
+            }
+
+        // This part is for constant function model
+        } else {
+            let constant = parsed.eval_with_context(ctx)?;
+
+            if prev.is_empty() {
+                output.values.push(constant);
+            }
+
+            output.values.extend(std::iter::repeat_n(constant, len));
+
+            if continuous {
+                output.path = vec![[0.0, constant], [len as f64, constant]];
+            }
+        }
+
+        Ok(output)
+    }
+}
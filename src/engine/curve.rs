@@ -0,0 +1,246 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What the values of a [`YieldCurve`] are.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    /// Zero rate per year, compounded annually (e.g. `0.035`).
+    #[default]
+    ZeroRate,
+    /// Discount factor, i.e. the present value of 1 paid at the tenor (e.g. `0.966`).
+    DiscountFactor,
+}
+
+/// How discount factors are interpolated between tenors.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    Linear,
+    /// Linear in `ln(discount factor)`, i.e. piecewise flat forward rates.
+    #[default]
+    LogLinear,
+}
+
+impl CurveKind {
+    pub const ALL: [CurveKind; 2] = [CurveKind::ZeroRate, CurveKind::DiscountFactor];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CurveKind::ZeroRate => "Zero Rate",
+            CurveKind::DiscountFactor => "Discount Factor",
+        }
+    }
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 2] = [Interpolation::Linear, Interpolation::LogLinear];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "Linear DF",
+            Interpolation::LogLinear => "Log-Linear DF",
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CurvePoint {
+    /// Tenor in years
+    pub tenor: String,
+    pub value: String,
+}
+
+/// Why an enabled [`YieldCurve`] is not used for discounting.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CurveError {
+    /// No point has a positive tenor and discount factor.
+    NoValidPoints,
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CurveError::NoValidPoints => {
+                write!(f, "The yield curve has no valid point; discounting at the row rates instead")
+            },
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
+
+/// Term structure used instead of the flat / per-row discount rates when `enabled`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YieldCurve {
+    pub enabled: bool,
+    pub kind: CurveKind,
+    pub interpolation: Interpolation,
+    pub points: Vec<CurvePoint>,
+}
+
+impl YieldCurve {
+    /// Reads `tenor,value` lines; blank lines and a non-numeric header line before the first
+    /// point are skipped.
+    pub fn parse_csv(text: &str) -> Result<Vec<CurvePoint>, String> {
+        let mut points = Vec::new();
+        let mut header = false;
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut fields = line.split([',', ';', '\t']).map(str::trim);
+            let (tenor, value) = match (fields.next(), fields.next()) {
+                (Some(tenor), Some(value)) if tenor.parse::<f64>().is_ok() && value.parse::<f64>().is_ok() => {
+                    (tenor, value)
+                },
+                _ if points.is_empty() && !header => {
+                    header = true;
+                    continue;
+                },
+                (Some(_), Some(_)) => {
+                    return Err(format!("line {}: `{}` is not a number pair", i + 1, line.trim()));
+                },
+                _ => return Err(format!("line {}: expected `tenor,value`", i + 1)),
+            };
+            points.push(CurvePoint { tenor: tenor.into(), value: value.into() });
+        }
+        Ok(points)
+    }
+
+    /// `(tenor, discount factor)` pairs sorted by tenor, starting with `(0, 1)`.
+    ///
+    /// Points that do not parse or have a non-positive tenor or discount factor are skipped.
+    pub fn discount_factors(&self) -> Vec<(f64, f64)> {
+        let mut nodes: Vec<(f64, f64)> = self.points.iter()
+            .filter_map(|p| Some((p.tenor.parse::<f64>().ok()?, p.value.parse::<f64>().ok()?)))
+            .filter(|&(tenor, _)| tenor > 0.0)
+            .map(|(tenor, value)| match self.kind {
+                CurveKind::ZeroRate => (tenor, (1.0 + value).powf(-tenor)),
+                CurveKind::DiscountFactor => (tenor, value),
+            })
+            .filter(|&(_, df)| df > 0.0 && df.is_finite())
+            .collect();
+        nodes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        nodes.dedup_by(|a, b| a.0 == b.0);
+        nodes.insert(0, (0.0, 1.0));
+        nodes
+    }
+
+    /// Why the curve cannot discount; `None` when it can or is disabled.
+    pub fn error(&self) -> Option<CurveError> {
+        (self.enabled && self.discount_factors().len() < 2).then_some(CurveError::NoValidPoints)
+    }
+}
+
+/// Interpolated discount factor at `t` from nodes produced by [`YieldCurve::discount_factors`].
+///
+/// Beyond the last tenor the last zero rate is held flat.
+pub fn interpolate(nodes: &[(f64, f64)], interpolation: Interpolation, t: f64) -> f64 {
+    let i = nodes.partition_point(|&(tenor, _)| tenor < t);
+    if i == 0 {
+        return 1.0;
+    }
+    let (t0, df0) = nodes[i - 1];
+    let Some(&(t1, df1)) = nodes.get(i) else {
+        return df0.powf(t / t0);
+    };
+
+    let w = (t - t0) / (t1 - t0);
+    match interpolation {
+        Interpolation::Linear => df0 + (df1 - df0) * w,
+        Interpolation::LogLinear => (df0.ln() + (df1.ln() - df0.ln()) * w).exp(),
+    }
+}
+
+/// Zero rate per year implied by a discount factor at `t` years.
+pub fn zero_rate(df: f64, t: f64) -> f64 {
+    df.powf(-1.0 / t) - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row, StateData};
+
+    fn pairs(points: &[CurvePoint]) -> Vec<(&str, &str)> {
+        points.iter().map(|p| (p.tenor.as_str(), p.value.as_str())).collect()
+    }
+
+    #[test]
+    fn parse_csv_skips_blank_lines_and_one_header() {
+        let points = YieldCurve::parse_csv("\n\ntenor,rate\n1, 0.03\n\n2;0.035\n5\t0.04\n").unwrap();
+        assert_eq!(pairs(&points), [("1", "0.03"), ("2", "0.035"), ("5", "0.04")]);
+        // a header without separator is skipped as well
+        let points = YieldCurve::parse_csv("Yield curve\n1,0.03").unwrap();
+        assert_eq!(pairs(&points), [("1", "0.03")]);
+    }
+
+    #[test]
+    fn parse_csv_rejects_bad_lines() {
+        let error = |text| YieldCurve::parse_csv(text).err().unwrap_or_default();
+        assert_eq!(error("tenor,rate\nyears,rate\n1,0.03"), "line 2: `years,rate` is not a number pair");
+        assert_eq!(error("1,0.03\n2,x"), "line 2: `2,x` is not a number pair");
+        assert_eq!(error("1,0.03\n2"), "line 2: expected `tenor,value`");
+    }
+
+    #[test]
+    fn interpolate_between_and_beyond_tenors() {
+        let nodes = [(0.0, 1.0), (1.0, 0.9), (3.0, 0.7)];
+        assert_close(interpolate(&nodes, Interpolation::Linear, 0.0), 1.0);
+        assert_close(interpolate(&nodes, Interpolation::Linear, 2.0), 0.8);
+        assert_close(interpolate(&nodes, Interpolation::LogLinear, 2.0), (0.9f64 * 0.7).sqrt());
+        assert_close(interpolate(&nodes, Interpolation::LogLinear, 0.5), 0.9f64.sqrt());
+        // past the last tenor the zero rate is held flat
+        assert_close(interpolate(&nodes, Interpolation::Linear, 6.0), 0.49);
+    }
+
+    #[test]
+    fn zero_rates_are_converted_to_discount_factors() {
+        let curve = YieldCurve {
+            points: vec![
+                CurvePoint { tenor: "2".into(), value: "0.05".into() },
+                CurvePoint { tenor: "1".into(), value: "0.04".into() },
+                CurvePoint { tenor: "-1".into(), value: "0.04".into() },
+            ],
+            ..Default::default()
+        };
+        let nodes = curve.discount_factors();
+        assert_eq!(nodes.len(), 3);
+        assert_close(nodes[1].1, 1.0 / 1.04);
+        assert_close(nodes[2].1, 1.0 / 1.05f64.powi(2));
+    }
+
+    #[test]
+    fn valuation_discounts_on_the_curve() {
+        let state = StateData {
+            rows: vec![row("2", "100")],
+            yield_curve: YieldCurve {
+                enabled: true,
+                kind: CurveKind::DiscountFactor,
+                points: vec![CurvePoint { tenor: "1".into(), value: "0.9".into() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let valuation = state.evaluate();
+        assert_eq!(valuation.curve_error, None);
+        assert_close(valuation.dcf[1].dcf_unit, 90.0);
+        assert_close(valuation.dcf[2].dcf_unit, 81.0);
+    }
+
+    #[test]
+    fn curve_without_points_falls_back_to_the_rates() {
+        let state = StateData {
+            rows: vec![row("2", "100")],
+            yield_curve: YieldCurve { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+        let valuation = state.evaluate();
+        assert_eq!(valuation.curve_error, Some(CurveError::NoValidPoints));
+        assert_close(valuation.dcf[2].dcf_unit, 100.0 / 1.03f64.powi(2));
+    }
+}
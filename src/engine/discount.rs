@@ -0,0 +1,417 @@
+use serde::{Deserialize, Serialize};
+
+use super::curve::{self, Interpolation};
+use super::YieldCurve;
+use super::{Cashflow, DcfData, Row, StateData};
+
+/// Discounting convention: when within period `t` its cash-flow is assumed to arrive.
+///
+/// The cash-flow at t = 0 is never discounted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timing {
+    /// Discounted by `discount ^ t`.
+    #[default]
+    EndOfPeriod,
+    /// Discounted by `discount ^ (t - 0.5)`.
+    MidPeriod,
+    /// Discounted by `discount ^ (t - 1)`.
+    BeginningOfPeriod,
+}
+
+/// How cash-flows are turned into present values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuationMode {
+    /// One cash-flow per period, discounted according to [`Timing`].
+    #[default]
+    Discrete,
+    /// The cash-flow is a rate: `∫ cashflow(t) / discount(t) dt` over each period.
+    Continuous,
+}
+
+impl ValuationMode {
+    pub const ALL: [ValuationMode; 2] = [ValuationMode::Discrete, ValuationMode::Continuous];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValuationMode::Discrete => "Discrete",
+            ValuationMode::Continuous => "Continuous",
+        }
+    }
+}
+
+impl Timing {
+    pub const ALL: [Timing; 3] = [Timing::EndOfPeriod, Timing::MidPeriod, Timing::BeginningOfPeriod];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Timing::EndOfPeriod => "End of Period",
+            Timing::MidPeriod => "Mid-Period",
+            Timing::BeginningOfPeriod => "Beginning of Period",
+        }
+    }
+
+    /// Time, in periods, at which the cash-flow of period `t` is discounted.
+    pub fn discount_time(&self, t: usize) -> f64 {
+        let shift = match self {
+            Timing::EndOfPeriod => 0.0,
+            Timing::MidPeriod => 0.5,
+            Timing::BeginningOfPeriod => 1.0,
+        };
+        (t as f64 - shift).max(0.0)
+    }
+}
+
+/// Cumulative discounting over time.
+#[derive(Clone, Debug)]
+pub struct DiscountCurve {
+    source: Source,
+    /// Time, in the units the rates are quoted in, of each period boundary; the period index
+    /// itself when empty. Filled with year fractions for sub-annual periods or calendar dates.
+    times: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+enum Source {
+    /// Compounded period by period.
+    Periodic {
+        /// `rates[k]` is the gross rate over period `k`; `rates[0]` is unused.
+        rates: Vec<f64>,
+        /// `cumulative[k]` is the divisor at the end of period `k`.
+        cumulative: Vec<f64>,
+        /// Gross rate of the periods after the last one in `cumulative`.
+        tail: f64,
+    },
+    /// Interpolated from a [`YieldCurve`].
+    Term {
+        nodes: Vec<(f64, f64)>,
+        interpolation: Interpolation,
+    },
+}
+
+impl Default for DiscountCurve {
+    fn default() -> Self {
+        Self::from_rates(&[], 1.0, Vec::new())
+    }
+}
+
+impl DiscountCurve {
+    /// `rates[k]` is the gross rate from `k - 1` to `k`; `rates[0]` is ignored.
+    ///
+    /// `times` are the period boundaries in the units the rates are quoted in (see
+    /// [`StateData::period_times`]); empty means one unit per period.
+    pub fn from_rates(rates: &[f64], tail: f64, times: Vec<f64>) -> Self {
+        let mut curve = Self {
+            source: Source::Periodic { rates: rates.to_vec(), cumulative: Vec::new(), tail },
+            times,
+        };
+        let mut cumulative = vec![1.0];
+        for (k, &rate) in rates.iter().enumerate().skip(1) {
+            let length = curve.time(k as f64) - curve.time((k - 1) as f64);
+            cumulative.push(cumulative.last().unwrap() * rate.powf(length));
+        }
+        if let Source::Periodic { cumulative: c, .. } = &mut curve.source {
+            *c = cumulative;
+        }
+        curve
+    }
+
+    /// Tenors are read in the units of `times`, like the rates of [`DiscountCurve::from_rates`].
+    pub fn from_yield_curve(curve: &YieldCurve, times: Vec<f64>) -> Self {
+        Self {
+            source: Source::Term { nodes: curve.discount_factors(), interpolation: curve.interpolation },
+            times,
+        }
+    }
+
+    /// Gross rate of the periods past the last one, which the perpetuity formulas of the
+    /// terminal value discount at; `None` for a yield curve.
+    pub fn tail_rate(&self) -> Option<f64> {
+        match &self.source {
+            Source::Periodic { tail, .. } => Some(*tail),
+            Source::Term { .. } => None,
+        }
+    }
+
+    /// Curve time of `t` periods, linear within a period and continuing with the last period's
+    /// length past the end of `times`.
+    fn time(&self, t: f64) -> f64 {
+        let times = &self.times;
+        if times.is_empty() {
+            return t;
+        }
+        let last = times.len() - 1;
+        let k = (t.floor().max(0.0) as usize).min(last);
+        let length = match (times.get(k + 1), k.checked_sub(1)) {
+            (Some(next), _) => next - times[k],
+            (None, Some(prev)) => times[k] - times[prev],
+            (None, None) => 1.0,
+        };
+        times[k] + length * (t - k as f64)
+    }
+
+    /// Divisor applied to a cash-flow at time `t` (in periods); fractional times compound within
+    /// the period.
+    pub fn factor(&self, t: f64) -> f64 {
+        let tau = self.time(t);
+        match &self.source {
+            Source::Periodic { rates, cumulative, tail } => {
+                let last = cumulative.len() - 1;
+                let whole = (t.floor().max(0.0) as usize).min(last);
+                let rate = if whole < last { rates[whole + 1] } else { *tail };
+                cumulative[whole] * rate.powf(tau - self.time(whole as f64))
+            },
+            Source::Term { nodes, interpolation } => 1.0 / curve::interpolate(nodes, *interpolation, tau),
+        }
+    }
+}
+
+impl StateData {
+    /// Discount curve of the scenario: the yield curve when enabled and usable (see
+    /// [`YieldCurve::error`]), otherwise each row's own rate over its periods. Rates are per year
+    /// and compound over the length of each period, or the exact year fractions between the
+    /// dates with the calendar on.
+    ///
+    /// Past the last period the curve continues at the last segment's rate, the rate of the
+    /// mature phase the terminal value covers. When that rate is an expression in `t`, its value
+    /// at the last period is held from there on.
+    pub fn discount_curve(&self, cashflow: &Cashflow) -> DiscountCurve {
+        let times = self.period_times(cashflow.values.len()).unwrap_or_default();
+        if self.yield_curve.enabled && self.yield_curve.error().is_none() {
+            DiscountCurve::from_yield_curve(&self.yield_curve, times)
+        } else {
+            DiscountCurve::from_rates(&cashflow.discount_rates, self.terminal_discount(cashflow), times)
+        }
+    }
+
+    /// Rows whose `discount` column is used: those of the discounted series, if any, or the
+    /// revenue rows in driver mode.
+    pub fn discount_rows(&self) -> &[Row] {
+        if !self.discounted_series.is_empty() {
+            self.series.iter()
+                .find(|s| s.name == self.discounted_series)
+                .map(|s| s.rows.as_slice())
+                .unwrap_or_default()
+        } else if self.drivers.enabled {
+            &self.drivers.revenue
+        } else {
+            &self.rows
+        }
+    }
+
+    /// Gross rate of the last segment; the global `discount` without any period.
+    pub fn terminal_discount(&self, cashflow: &Cashflow) -> f64 {
+        match cashflow.discount_rates.get(1..) {
+            Some([.., last]) => *last,
+            _ => self.discount.parse().unwrap_or(1.0),
+        }
+    }
+
+    /// Time, in periods, at which the cash-flow of period `t` is discounted.
+    pub fn discount_time(&self, t: usize) -> f64 {
+        match self.valuation_mode {
+            ValuationMode::Discrete => self.timing.discount_time(t),
+            ValuationMode::Continuous => t as f64,
+        }
+    }
+
+    /// Unit DCF of each period according to the [`ValuationMode`], with the running sum.
+    pub fn calculate_dcf(&self, curve: &DiscountCurve, cashflow: &Cashflow) -> Vec<DcfData> {
+        match self.valuation_mode {
+            ValuationMode::Discrete => self.calculate_discrete_dcf(curve, &cashflow.values),
+            ValuationMode::Continuous => self.calculate_continuous_dcf(curve, cashflow),
+        }
+    }
+
+    /// Present value of the continuous path over each period `(t - 1, t]`, integrated with the
+    /// trapezoidal rule at [`StateData::step_size`]. Period 0 has no length and is worth nothing.
+    fn calculate_continuous_dcf(&self, curve: &DiscountCurve, cashflow: &Cashflow) -> Vec<DcfData> {
+        let path = &cashflow.path;
+        // linear interpolation of the path
+        let at = |s: f64| {
+            let i = path.partition_point(|p| p[0] < s);
+            match (i.checked_sub(1).map(|j| path[j]), path.get(i).copied()) {
+                (Some([t0, y0]), Some([t1, y1])) if t1 > t0 => y0 + (y1 - y0) * (s - t0) / (t1 - t0),
+                (_, Some([_, y])) | (Some([_, y]), None) => y,
+                (None, None) => 0.0,
+            }
+        };
+        let pv_rate = |s: f64| at(s) / curve.factor(s);
+
+        let steps = (1.0 / self.step_size()).ceil().max(1.0) as usize;
+        let h = 1.0 / steps as f64;
+
+        let mut output = Vec::new();
+        let mut dcf_sum = 0.0;
+        for (t, &cashflow) in cashflow.values.iter().enumerate() {
+            let dcf_unit = if t == 0 {
+                0.0
+            } else {
+                let from = (t - 1) as f64;
+                (0..steps)
+                    .map(|k| from + k as f64 * h)
+                    .map(|s| (pv_rate(s) + pv_rate(s + h)) * h / 2.0)
+                    .sum()
+            };
+            dcf_sum += dcf_unit;
+            output.push(DcfData { cashflow, dcf_unit, dcf_sum });
+        }
+        output
+    }
+
+    /// Discounts each cash-flow according to [`Timing`] and accumulates the running sum.
+    fn calculate_discrete_dcf(&self, curve: &DiscountCurve, cashflow: &[f64]) -> Vec<DcfData> {
+        let mut output = Vec::new();
+        let mut dcf_sum = 0.0;
+        for (t, &cashflow) in cashflow.iter().enumerate() {
+            let dcf_unit = cashflow / curve.factor(self.discount_time(t));
+            dcf_sum += dcf_unit;
+            output.push(DcfData { cashflow, dcf_unit, dcf_sum });
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        assert_close, tests::row, DiscountCurve, StateData, TerminalInputs, TerminalMethod, Timing, ValuationMode,
+    };
+
+    fn state(timing: Timing, method: TerminalMethod) -> StateData {
+        StateData {
+            rows: vec![row("2", "100")],
+            growth: "1.0".into(),
+            discount: "1.1".into(),
+            timing,
+            terminal: TerminalInputs { method, exit_multiple: "5".into(), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cashflows_follow_the_timing() {
+        for (timing, shift) in [(Timing::EndOfPeriod, 0.0), (Timing::MidPeriod, 0.5), (Timing::BeginningOfPeriod, 1.0)] {
+            let valuation = state(timing, TerminalMethod::PerpetualGrowth).evaluate();
+            // t = 0 is never discounted
+            assert_close(valuation.dcf[0].dcf_unit, 100.0);
+            assert_close(valuation.dcf[1].dcf_unit, 100.0 / 1.1f64.powf(1.0 - shift));
+            assert_close(valuation.dcf[2].dcf_unit, 100.0 / 1.1f64.powf(2.0 - shift));
+        }
+    }
+
+    #[test]
+    fn terminal_value_timing() {
+        // growth methods follow the timing, an exit happens at the end of the last period
+        let tv = state(Timing::MidPeriod, TerminalMethod::PerpetualGrowth).evaluate().terminal_value.unwrap();
+        assert_close(tv.pv, tv.value / 1.1f64.powf(1.5));
+        let tv = state(Timing::MidPeriod, TerminalMethod::ExitMultiple).evaluate().terminal_value.unwrap();
+        assert_close(tv.pv, 500.0 / 1.1f64.powi(2));
+    }
+
+    #[test]
+    fn factor_compounds_each_segment_at_its_rate() {
+        let curve = DiscountCurve::from_rates(&[1.0, 1.1, 1.1, 1.2], 1.3, Vec::new());
+        assert_close(curve.factor(0.0), 1.0);
+        assert_close(curve.factor(2.0), 1.1 * 1.1);
+        assert_close(curve.factor(2.5), 1.1 * 1.1 * 1.2f64.sqrt());
+        assert_close(curve.factor(3.0), 1.1 * 1.1 * 1.2);
+        // past the last period the tail rate continues
+        assert_close(curve.factor(4.5), 1.1 * 1.1 * 1.2 * 1.3f64.powf(1.5));
+    }
+
+    #[test]
+    fn factor_follows_period_times() {
+        // a half-year period followed by a full year, rates per year
+        let curve = DiscountCurve::from_rates(&[1.0, 1.1, 1.2], 1.2, vec![0.0, 0.5, 1.5]);
+        assert_close(curve.factor(1.0), 1.1f64.sqrt());
+        assert_close(curve.factor(1.5), 1.1f64.sqrt() * 1.2f64.sqrt());
+        assert_close(curve.factor(2.0), 1.1f64.sqrt() * 1.2);
+        // past the end the last period length continues
+        assert_close(curve.factor(3.0), 1.1f64.sqrt() * 1.2 * 1.2);
+    }
+
+    #[test]
+    fn rows_discount_at_their_own_rate() {
+        let mut state = state(Timing::MidPeriod, TerminalMethod::PerpetualGrowth);
+        state.rows = vec![row("2", "100"), row("4", "100")];
+        state.rows[0].discount = "1.1".into();
+        state.rows[1].discount = "1.1 + 0.05 * t".into();
+        let valuation = state.evaluate();
+        let units: Vec<f64> = valuation.dcf.iter().map(|d| d.dcf_unit).collect();
+        // t counts from 1 within the second segment: 1.15, then 1.2
+        let expected = [
+            100.0,
+            100.0 / 1.1f64.sqrt(),
+            100.0 / 1.1f64.powf(1.5),
+            100.0 / (1.1 * 1.1 * 1.15f64.sqrt()),
+            100.0 / (1.1 * 1.1 * 1.15 * 1.2f64.sqrt()),
+        ];
+        assert_eq!(units.len(), expected.len());
+        for (actual, expected) in units.into_iter().zip(expected) {
+            assert_close(actual, expected);
+        }
+    }
+
+    #[test]
+    fn terminal_value_uses_the_last_segment_rate() {
+        let mut state = state(Timing::EndOfPeriod, TerminalMethod::PerpetualGrowth);
+        state.rows = vec![row("1", "100"), row("2", "100")];
+        state.rows[1].discount = "1.2".into();
+        let valuation = state.evaluate();
+        assert_eq!(valuation.discount_curve.tail_rate(), Some(1.2));
+        let tv = valuation.terminal_value.unwrap();
+        assert_close(tv.value, 100.0 / 0.2);
+        assert_close(tv.pv, tv.value / (1.1 * 1.2));
+    }
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6 * expected.abs(), "{actual} != {expected}");
+    }
+
+    #[test]
+    fn continuous_mode_integrates_each_period() {
+        let mut state = state(Timing::MidPeriod, TerminalMethod::PerpetualGrowth);
+        state.valuation_mode = ValuationMode::Continuous;
+        let valuation = state.evaluate();
+        // t = 0 has no length; timing does not apply
+        assert_eq!(valuation.dcf[0].dcf_unit, 0.0);
+        let ln = 1.1f64.ln();
+        for t in 1..=2 {
+            let expected = 100.0 * (1.1f64.powi(1 - t) - 1.1f64.powi(-t)) / ln;
+            assert_near(valuation.dcf[t as usize].dcf_unit, expected);
+        }
+        // the perpetuity is integrated as well: ∫ 100 / 1.1 ^ s ds
+        let tv = valuation.terminal_value.unwrap();
+        assert_near(tv.value, 100.0 / ln);
+        assert_near(tv.pv, tv.value / 1.21);
+    }
+
+    #[test]
+    fn continuous_mode_follows_time_functions_and_odes() {
+        let mut state = state(Timing::EndOfPeriod, TerminalMethod::PerpetualGrowth);
+        state.valuation_mode = ValuationMode::Continuous;
+        // growing at the discount rate: every period is worth the initial rate
+        state.rows = vec![row("2", "100 * 1.1^t")];
+        let valuation = state.evaluate();
+        assert_near(valuation.dcf[1].dcf_unit, 100.0);
+        assert_near(valuation.dcf[2].dcf_unit, 100.0);
+
+        state.rows = vec![row("0", "100"), row("2", "ln(1.1) * y")];
+        let valuation = state.evaluate();
+        assert!((valuation.dcf[2].dcf_unit - 100.0).abs() < 1e-3, "{}", valuation.dcf[2].dcf_unit);
+    }
+
+    #[test]
+    fn continuous_horizon() {
+        let mut state = state(Timing::EndOfPeriod, TerminalMethod::PerpetualGrowth);
+        state.valuation_mode = ValuationMode::Continuous;
+        state.terminal.horizon = "3".into();
+        let tv = state.evaluate().terminal_value.unwrap();
+        assert_near(tv.value, 100.0 * (1.0 - 1.1f64.powi(-3)) / 1.1f64.ln());
+        state.growth = "1.1".into();
+        let tv = state.evaluate().terminal_value.unwrap();
+        assert_near(tv.value, 300.0);
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Cashflow, NamedSeries, Row, RowError, StateData, ValuationMode};
+
+/// Operating drivers of the unlevered free cash flow.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Driver {
+    Revenue,
+    /// Decimal, e.g. `0.15`
+    EbitMargin,
+    /// Decimal, e.g. `0.25`
+    TaxRate,
+    Depreciation,
+    Capex,
+    NwcChange,
+}
+
+impl Driver {
+    pub const ALL: [Driver; 6] = [
+        Driver::Revenue,
+        Driver::EbitMargin,
+        Driver::TaxRate,
+        Driver::Depreciation,
+        Driver::Capex,
+        Driver::NwcChange,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Driver::Revenue => "Revenue",
+            Driver::EbitMargin => "EBIT Margin",
+            Driver::TaxRate => "Tax Rate",
+            Driver::Depreciation => "D&A",
+            Driver::Capex => "Capex",
+            Driver::NwcChange => "ΔNWC",
+        }
+    }
+}
+
+/// Piecewise rows of each driver; when `enabled` they replace [`StateData::rows`] with
+/// `revenue * ebit_margin * (1 - tax_rate) + depreciation - capex - nwc_change`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Drivers {
+    pub enabled: bool,
+    pub revenue: Vec<Row>,
+    pub ebit_margin: Vec<Row>,
+    pub tax_rate: Vec<Row>,
+    pub depreciation: Vec<Row>,
+    pub capex: Vec<Row>,
+    pub nwc_change: Vec<Row>,
+}
+
+impl Drivers {
+    pub fn rows(&self, driver: Driver) -> &Vec<Row> {
+        match driver {
+            Driver::Revenue => &self.revenue,
+            Driver::EbitMargin => &self.ebit_margin,
+            Driver::TaxRate => &self.tax_rate,
+            Driver::Depreciation => &self.depreciation,
+            Driver::Capex => &self.capex,
+            Driver::NwcChange => &self.nwc_change,
+        }
+    }
+
+    pub fn rows_mut(&mut self, driver: Driver) -> &mut Vec<Row> {
+        match driver {
+            Driver::Revenue => &mut self.revenue,
+            Driver::EbitMargin => &mut self.ebit_margin,
+            Driver::TaxRate => &mut self.tax_rate,
+            Driver::Depreciation => &mut self.depreciation,
+            Driver::Capex => &mut self.capex,
+            Driver::NwcChange => &mut self.nwc_change,
+        }
+    }
+}
+
+/// Evaluated driver columns, aligned with the free cash flow.
+#[derive(Clone, Default, Serialize)]
+pub struct DriverSeries {
+    pub revenue: Vec<f64>,
+    pub ebit_margin: Vec<f64>,
+    pub tax_rate: Vec<f64>,
+    pub depreciation: Vec<f64>,
+    pub capex: Vec<f64>,
+    pub nwc_change: Vec<f64>,
+    /// Diagnostics of each driver's rows, in [`Driver::ALL`] order.
+    pub row_errors: Vec<Vec<Option<RowError>>>,
+}
+
+impl DriverSeries {
+    pub fn column(&self, driver: Driver) -> &[f64] {
+        match driver {
+            Driver::Revenue => &self.revenue,
+            Driver::EbitMargin => &self.ebit_margin,
+            Driver::TaxRate => &self.tax_rate,
+            Driver::Depreciation => &self.depreciation,
+            Driver::Capex => &self.capex,
+            Driver::NwcChange => &self.nwc_change,
+        }
+    }
+
+    pub fn row_errors(&self, driver: Driver) -> &[Option<RowError>] {
+        let i = Driver::ALL.iter().position(|&d| d == driver).unwrap();
+        self.row_errors.get(i).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl StateData {
+    /// Free cash flow built from the drivers.
+    ///
+    /// Revenue sets the horizon and the per-segment discount rates; a shorter driver holds its
+    /// last value and an empty one is zero. The continuous path, if needed, runs straight
+    /// between the period values. The drivers can refer to the evaluated `series`.
+    pub fn calculate_free_cashflow(&self, series: &[NamedSeries]) -> Cashflow {
+        let revenue = self.calculate_series(&self.drivers.revenue, series);
+        let len = revenue.values.len();
+
+        let mut row_errors = vec![revenue.row_errors];
+        let mut columns = Vec::new();
+        for driver in &Driver::ALL[1..] {
+            let mut column = self.calculate_series(self.drivers.rows(*driver), series);
+            let last = column.values.last().copied().unwrap_or(0.0);
+            column.values.resize(len, last);
+            columns.push(column.values);
+            row_errors.push(column.row_errors);
+        }
+        let [ebit_margin, tax_rate, depreciation, capex, nwc_change]: [Vec<f64>; 5] = columns.try_into().unwrap();
+
+        let values: Vec<f64> = (0..len)
+            .map(|t| {
+                revenue.values[t] * ebit_margin[t] * (1.0 - tax_rate[t]) + depreciation[t] - capex[t] - nwc_change[t]
+            })
+            .collect();
+        let path = match self.valuation_mode {
+            ValuationMode::Continuous => values.iter().enumerate().map(|(t, &y)| [t as f64, y]).collect(),
+            ValuationMode::Discrete => Vec::new(),
+        };
+
+        Cashflow {
+            values,
+            discount_rates: revenue.discount_rates,
+            row_errors: Vec::new(),
+            path,
+            drivers: Some(DriverSeries {
+                revenue: revenue.values,
+                ebit_margin,
+                tax_rate,
+                depreciation,
+                capex,
+                nwc_change,
+                row_errors,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row};
+
+    #[test]
+    fn free_cashflow() {
+        let drivers = Drivers {
+            enabled: true,
+            revenue: vec![row("1", "1000"), row("3", "1000 * 1.1^t")],
+            ebit_margin: vec![row("3", "0.2")],
+            tax_rate: vec![row("3", "0.25")],
+            depreciation: vec![row("3", "50")],
+            capex: vec![row("3", "80")],
+            // shorter than revenue: holds its last value
+            nwc_change: vec![row("1", "10 + 5 * t")],
+        };
+        let state = StateData { drivers, ..Default::default() };
+        let cashflow = state.calculate_cashflow();
+        let drivers = cashflow.drivers.unwrap();
+        assert_eq!(drivers.nwc_change, [10.0, 15.0, 15.0, 15.0]);
+        for (t, revenue) in [1000.0, 1000.0, 1100.0, 1210.0].into_iter().enumerate() {
+            assert_close(drivers.revenue[t], revenue);
+            assert_close(cashflow.values[t], revenue * 0.2 * 0.75 + 50.0 - 80.0 - drivers.nwc_change[t]);
+        }
+    }
+
+    #[test]
+    fn empty_drivers_count_as_zero() {
+        let mut state = StateData::default();
+        state.drivers.enabled = true;
+        state.drivers.revenue = vec![row("2", "1000")];
+        state.drivers.ebit_margin = vec![row("2", "0.1")];
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.values, [100.0; 3]);
+    }
+
+    #[test]
+    fn revenue_carries_the_discount_rates_and_errors_stay_per_driver() {
+        let mut state = StateData::default();
+        state.drivers.enabled = true;
+        state.drivers.revenue = vec![Row { discount: "1.1".into(), ..row("2", "1000") }];
+        state.drivers.capex = vec![row("2", "x")];
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.discount_rates, [1.0, 1.1, 1.1]);
+        let drivers = cashflow.drivers.unwrap();
+        assert_eq!(drivers.row_errors(Driver::Revenue), [None]);
+        assert_eq!(drivers.row_errors(Driver::Capex), [Some(RowError::UnknownVariable { name: "x".into() })]);
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+use meval::ParseError;
+use serde::Serialize;
+
+/// Why a single [`Row`](super::Row) could not be evaluated.
+///
+/// A failing row still occupies its periods (filled with `0.0`) so the rows after it keep their
+/// place on the time axis.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RowError {
+    /// `end` is empty or not a non-negative integer.
+    InvalidPeriod { end: String },
+    /// `end` is smaller than the end of the previous row.
+    NonMonotonicPeriod { end: usize, previous: usize },
+    /// The expression uses a name that is neither a variable of the model nor a known constant.
+    UnknownVariable { name: String },
+    /// The expression could not be parsed. `position` is the byte offset, when known.
+    Syntax { position: Option<usize>, message: String },
+    /// A function was called with the wrong number of arguments.
+    Function { name: String, message: String },
+    /// The ODE solver gave up on the segment.
+    Solver { message: String },
+    /// The row's own discount rate could not be evaluated; the global rate is used instead.
+    Discount { message: String },
+    /// `name(t)` in a segment starting at `start` under local time: the named series takes an
+    /// absolute period, while `t` restarts at 0 in every segment.
+    LocalSeriesTime { name: String, start: usize },
+}
+
+impl From<meval::Error> for RowError {
+    fn from(e: meval::Error) -> Self {
+        match e {
+            meval::Error::UnknownVariable(name) => RowError::UnknownVariable { name },
+            meval::Error::Function(name, e) => RowError::Function { name, message: e.to_string() },
+            meval::Error::ParseError(e) => {
+                let position = match e {
+                    ParseError::UnexpectedToken(i) => Some(i),
+                    _ => None,
+                };
+                RowError::Syntax { position, message: e.to_string() }
+            },
+            meval::Error::RPNError(e) => RowError::Syntax { position: None, message: e.to_string() },
+        }
+    }
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RowError::InvalidPeriod { end } if end.is_empty() => write!(f, "End of period is missing"),
+            RowError::InvalidPeriod { end } => write!(f, "Invalid end of period `{end}`"),
+            RowError::NonMonotonicPeriod { end, previous } => {
+                write!(f, "End {end} is before the previous end {previous}")
+            },
+            RowError::UnknownVariable { name } => write!(f, "Unknown variable `{name}`"),
+            RowError::Syntax { message, .. } => write!(f, "Syntax error: {message}"),
+            RowError::Function { name, message } => write!(f, "Function `{name}`: {message}"),
+            RowError::Solver { message } => write!(f, "ODE solver failed: {message}"),
+            RowError::Discount { message } => write!(f, "Discount rate: {message}"),
+            RowError::LocalSeriesTime { name, start } => {
+                write!(f, "`{name}(t)` reads period t of the series, but t restarts at 0 here; write `{name}(T)` or `{name}(t + {start})`")
+            },
+        }
+    }
+}
+
+impl std::error::Error for RowError {}
@@ -0,0 +1,385 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Assumption, StateData};
+
+/// Inputs of the goal seek that backs assumptions out of a market value.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GoalSeekInputs {
+    /// Enterprise value to match; empty uses the market price and share count of the equity bridge.
+    pub market_value: String,
+    /// Range of gross discount factors searched for the implied rate.
+    pub discount_min: String,
+    pub discount_max: String,
+    /// Range of gross terminal growth factors searched for the implied growth.
+    pub growth_min: String,
+    pub growth_max: String,
+    /// 1-based row whose expression is scaled; empty skips the multiplier.
+    pub row: String,
+    /// Range searched for the multiplier of `row`'s expression.
+    pub multiplier_min: String,
+    pub multiplier_max: String,
+}
+
+impl Default for GoalSeekInputs {
+    fn default() -> Self {
+        Self {
+            market_value: "".into(),
+            discount_min: "1.0".into(),
+            discount_max: "1.5".into(),
+            growth_min: "0.9".into(),
+            growth_max: "1.2".into(),
+            row: "".into(),
+            multiplier_min: "0".into(),
+            multiplier_max: "5".into(),
+        }
+    }
+}
+
+/// Value at which the DCF result matches the target.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Root {
+    pub value: f64,
+    pub iterations: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GoalSeekError {
+    /// Neither a market value nor a market price with a share count is given.
+    NoTarget,
+    /// The row to scale does not exist.
+    InvalidRow { row: String },
+    /// The rows are not what is valued: the drivers or a discounted series replace them.
+    RowsNotValued,
+    /// The search range does not parse or is empty.
+    InvalidRange,
+    /// The rows at these indices set their own discount rate, which the solved rate would not
+    /// replace.
+    RowDiscountRates { rows: Vec<usize> },
+    /// The enabled yield curve discounts instead of the solved rate.
+    YieldCurve,
+    /// The DCF result does not cross the target anywhere in the range.
+    NoRoot { min: f64, max: f64 },
+    /// The DCF result crosses the target more than once.
+    MultipleRoots { roots: Vec<f64> },
+    /// The valuation became invalid while narrowing down the root.
+    NotConverged { last: f64 },
+}
+
+impl fmt::Display for GoalSeekError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoalSeekError::NoTarget => write!(f, "Enter a market value, or a market price and share count"),
+            GoalSeekError::InvalidRow { row } => write!(f, "There is no row `{row}`"),
+            GoalSeekError::RowsNotValued => write!(f, "The rows are not valued while drivers or a discounted series replace them"),
+            GoalSeekError::InvalidRange => write!(f, "Invalid search range"),
+            GoalSeekError::RowDiscountRates { rows } => {
+                let list: Vec<String> = rows.iter().map(|i| (i + 1).to_string()).collect();
+                match rows.len() {
+                    1 => write!(f, "Row {} sets its own discount rate; clear it to solve for one rate", list[0]),
+                    _ => write!(f, "Rows {} set their own discount rates; clear them to solve for one rate", list.join(", ")),
+                }
+            },
+            GoalSeekError::YieldCurve => write!(f, "The yield curve discounts instead; disable it to solve for one rate"),
+            GoalSeekError::NoRoot { min, max } => write!(f, "No value between {min} and {max} matches the target"),
+            GoalSeekError::MultipleRoots { roots } => {
+                let roots: Vec<String> = roots.iter().map(|r| format!("{r:.6}")).collect();
+                write!(f, "Several values match the target: {}", roots.join(", "))
+            },
+            GoalSeekError::NotConverged { last } => write!(f, "Did not converge (last value {last})"),
+        }
+    }
+}
+
+impl std::error::Error for GoalSeekError {}
+
+/// Points the range is scanned at for sign changes before bisecting.
+const SAMPLES: usize = 100;
+const MAX_ITERATIONS: usize = 200;
+
+impl StateData {
+    /// Enterprise value the goal seek aims at.
+    pub fn target_value(&self) -> Option<f64> {
+        self.goal_seek.market_value.parse().ok().or_else(|| self.bridge.market_enterprise_value())
+    }
+
+    /// Finds the value in `[min, max]` that, written into a copy of the scenario by `set`,
+    /// makes the DCF result equal `target`.
+    ///
+    /// The range is scanned for sign changes first, so several crossings are reported instead
+    /// of silently picking one; each crossing is then bisected.
+    pub fn solve_for(
+        &self,
+        target: f64,
+        min: f64,
+        max: f64,
+        set: impl Fn(&mut StateData, f64),
+    ) -> Result<Root, GoalSeekError> {
+        if min.partial_cmp(&max) != Some(std::cmp::Ordering::Less) {
+            return Err(GoalSeekError::InvalidRange);
+        }
+        let gap = |x: f64| {
+            let mut state = self.clone();
+            set(&mut state, x);
+            state.evaluate().total.filter(|v| v.is_finite()).map(|v| v - target)
+        };
+
+        let samples: Vec<(f64, Option<f64>)> = (0..=SAMPLES)
+            .map(|i| min + (max - min) * i as f64 / SAMPLES as f64)
+            .map(|x| (x, gap(x)))
+            .collect();
+
+        let mut roots = Vec::new();
+        for (i, w) in samples.windows(2).enumerate() {
+            let ((a, fa), (b, fb)) = (w[0], w[1]);
+            match (fa, fb) {
+                (Some(0.0), _) => roots.push(Ok(Root { value: a, iterations: 0 })),
+                (Some(fa), Some(fb)) if fa.signum() != fb.signum() && fb != 0.0 => {
+                    roots.push(bisect(&gap, a, b, fa));
+                },
+                _ => {},
+            }
+            // an exact hit on the last sample
+            if i == SAMPLES - 1 && fb == Some(0.0) {
+                roots.push(Ok(Root { value: b, iterations: 0 }));
+            }
+        }
+
+        match roots.len() {
+            0 => Err(GoalSeekError::NoRoot { min, max }),
+            1 => roots.pop().unwrap(),
+            _ => Err(GoalSeekError::MultipleRoots {
+                roots: roots.iter().map(|r| match r {
+                    Ok(root) => root.value,
+                    Err(GoalSeekError::NotConverged { last }) => *last,
+                    Err(_) => f64::NAN,
+                }).collect(),
+            }),
+        }
+    }
+
+    /// Gross discount factor at which the DCF result equals the [`target_value`](Self::target_value).
+    pub fn implied_discount(&self) -> Result<Root, GoalSeekError> {
+        let target = self.target_value().ok_or(GoalSeekError::NoTarget)?;
+        if self.yield_curve.enabled && self.yield_curve.error().is_none() {
+            return Err(GoalSeekError::YieldCurve);
+        }
+        let rows: Vec<usize> = self.discount_rows()
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !row.discount.trim().is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        if !rows.is_empty() {
+            return Err(GoalSeekError::RowDiscountRates { rows });
+        }
+        let (Ok(min), Ok(max)) = (self.goal_seek.discount_min.parse(), self.goal_seek.discount_max.parse()) else {
+            return Err(GoalSeekError::InvalidRange);
+        };
+        // also switches the WACC builder off, which would override the rate
+        self.solve_for(target, min, max, |state, x| {
+            if let Some(discount) = state.assumption_mut(&Assumption::Discount) {
+                *discount = x.to_string();
+            }
+        })
+    }
+
+    /// Terminal growth factor at which the DCF result equals the [`target_value`](Self::target_value),
+    /// i.e. the growth the market is pricing in.
+    pub fn implied_growth(&self) -> Result<Root, GoalSeekError> {
+        let target = self.target_value().ok_or(GoalSeekError::NoTarget)?;
+        let (Ok(min), Ok(max)) = (self.goal_seek.growth_min.parse(), self.goal_seek.growth_max.parse()) else {
+            return Err(GoalSeekError::InvalidRange);
+        };
+        self.solve_for(target, min, max, |state, x| state.growth = x.to_string())
+    }
+
+    /// Multiplier on the expression of the chosen row at which the DCF result equals the
+    /// [`target_value`](Self::target_value). The whole expression is scaled, so for an ODE row it
+    /// is the rate of change that is scaled. Only the rows themselves can be scaled, not the
+    /// drivers valued instead.
+    pub fn implied_multiplier(&self) -> Result<Root, GoalSeekError> {
+        let target = self.target_value().ok_or(GoalSeekError::NoTarget)?;
+        if self.drivers.enabled || !self.discounted_series.is_empty() {
+            return Err(GoalSeekError::RowsNotValued);
+        }
+        let row = match self.goal_seek.row.parse::<usize>() {
+            Ok(row) if (1..=self.rows.len()).contains(&row) => row - 1,
+            _ => return Err(GoalSeekError::InvalidRow { row: self.goal_seek.row.clone() }),
+        };
+        let (Ok(min), Ok(max)) = (self.goal_seek.multiplier_min.parse(), self.goal_seek.multiplier_max.parse()) else {
+            return Err(GoalSeekError::InvalidRange);
+        };
+        let expr = &self.rows[row].expr;
+        self.solve_for(target, min, max, |state, x| state.rows[row].expr = format!("({expr}) * ({x})"))
+    }
+}
+
+/// Bisects `[a, b]`, over which `gap` changes sign, down to `f64` precision.
+fn bisect(gap: &impl Fn(f64) -> Option<f64>, mut a: f64, mut b: f64, mut fa: f64) -> Result<Root, GoalSeekError> {
+    for iterations in 1..=MAX_ITERATIONS {
+        let mid = (a + b) / 2.0;
+        if mid <= a || mid >= b {
+            return Ok(Root { value: mid, iterations });
+        }
+        match gap(mid) {
+            Some(0.0) => return Ok(Root { value: mid, iterations }),
+            Some(fm) if fm.signum() == fa.signum() => {
+                a = mid;
+                fa = fm;
+            },
+            Some(_) => b = mid,
+            None => return Err(GoalSeekError::NotConverged { last: mid }),
+        }
+    }
+    Err(GoalSeekError::NotConverged { last: (a + b) / 2.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row, CurvePoint, Series, TerminalMethod};
+
+    fn scenario(market_value: &str) -> StateData {
+        let mut state = StateData { rows: vec![row("3", "100")], ..Default::default() };
+        state.goal_seek.market_value = market_value.into();
+        state
+    }
+
+    #[test]
+    fn implied_discount_matches_the_market_value() {
+        let mut state = scenario("");
+        state.discount = "1.08".into();
+        state.goal_seek.market_value = state.evaluate().total.unwrap().to_string();
+        state.discount = "1.03".into();
+        // the WACC builder is switched off while solving
+        state.wacc.enabled = true;
+        assert_close(state.implied_discount().unwrap().value, 1.08);
+    }
+
+    #[test]
+    fn target_from_the_bridge() {
+        let mut state = scenario("");
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::NoTarget));
+        state.bridge.market_price = "20".into();
+        state.bridge.diluted_shares = "100".into();
+        assert_eq!(state.target_value(), Some(2000.0));
+    }
+
+    #[test]
+    fn no_root() {
+        let state = scenario("1e12");
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::NoRoot { min: 1.0, max: 1.5 }));
+    }
+
+    #[test]
+    fn multiple_roots() {
+        // -100 + 230 / d - 132 / d^2 is zero at d = 1.1 and d = 1.2
+        let mut state = scenario("0");
+        state.rows = vec![row("0", "-100"), row("1", "230"), row("2", "-132")];
+        state.terminal.method = TerminalMethod::ExitMultiple;
+        state.terminal.exit_multiple = "0".into();
+        state.goal_seek.discount_min = "1.05".into();
+        state.goal_seek.discount_max = "1.45".into();
+        let Err(GoalSeekError::MultipleRoots { roots }) = state.implied_discount() else {
+            panic!("expected two roots");
+        };
+        assert_eq!(roots.len(), 2);
+        assert_close(roots[0], 1.1);
+        assert_close(roots[1], 1.2);
+
+        // narrowing the range picks one
+        state.goal_seek.discount_max = "1.15".into();
+        assert_close(state.implied_discount().unwrap().value, 1.1);
+    }
+
+    #[test]
+    fn invalid_range() {
+        let mut state = scenario("1000");
+        state.goal_seek.discount_min = "1.5".into();
+        state.goal_seek.discount_max = "1.0".into();
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::InvalidRange));
+        state.goal_seek.discount_max = "".into();
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::InvalidRange));
+    }
+
+    #[test]
+    fn rows_with_their_own_discount_rate() {
+        let mut state = scenario("1000");
+        state.rows = vec![row("1", "100"), row("2", "100"), row("3", "100")];
+        state.rows[1].discount = "1.1".into();
+        state.rows[2].discount = " ".into();
+        let error = state.implied_discount().unwrap_err();
+        assert_eq!(error, GoalSeekError::RowDiscountRates { rows: vec![1] });
+        assert_eq!(error.to_string(), "Row 2 sets its own discount rate; clear it to solve for one rate");
+    }
+
+    #[test]
+    fn yield_curve_discounts() {
+        let mut state = scenario("1000");
+        state.yield_curve.enabled = true;
+        // a curve without points falls back to the rates, so the rate can be solved
+        assert!(state.implied_discount().is_ok());
+        state.yield_curve.points.push(CurvePoint { tenor: "1".into(), value: "0.04".into() });
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::YieldCurve));
+    }
+
+    #[test]
+    fn implied_growth_matches_the_market_value() {
+        let mut state = scenario("");
+        state.growth = "1.05".into();
+        state.discount = "1.08".into();
+        state.goal_seek.market_value = state.evaluate().total.unwrap().to_string();
+        state.growth = "1.02".into();
+        assert_close(state.implied_growth().unwrap().value, 1.05);
+    }
+
+    #[test]
+    fn implied_multiplier_scales_the_row() {
+        let mut state = scenario("");
+        state.rows = vec![row("1", "100"), row("3", "50 + 10 * t")];
+        state.goal_seek.market_value = StateData {
+            rows: vec![row("1", "100"), row("3", "(50 + 10 * t) * 2.5")],
+            ..state.clone()
+        }.evaluate().total.unwrap().to_string();
+        state.goal_seek.row = "2".into();
+        assert_close(state.implied_multiplier().unwrap().value, 2.5);
+
+        // a negative multiplier is found as well
+        state.goal_seek.market_value = "0".into();
+        state.goal_seek.row = "1".into();
+        state.goal_seek.multiplier_min = "-50".into();
+        let root = state.implied_multiplier().unwrap().value;
+        let mut scaled = state.clone();
+        scaled.rows[0].expr = format!("100 * ({root})");
+        assert!(scaled.evaluate().total.unwrap().abs() < 1e-6);
+        assert!(root < 0.0);
+    }
+
+    #[test]
+    fn implied_multiplier_needs_a_valued_row() {
+        let mut state = scenario("1000");
+        state.goal_seek.row = "2".into();
+        assert_eq!(state.implied_multiplier().err(), Some(GoalSeekError::InvalidRow { row: "2".into() }));
+        state.goal_seek.row = "1".into();
+        state.drivers.enabled = true;
+        assert_eq!(state.implied_multiplier().err(), Some(GoalSeekError::RowsNotValued));
+        state.drivers.enabled = false;
+        state.discounted_series = "revenue".into();
+        assert_eq!(state.implied_multiplier().err(), Some(GoalSeekError::RowsNotValued));
+    }
+
+    #[test]
+    fn discounted_series_rows_set_the_discount_rates() {
+        let mut state = scenario("1000");
+        state.series = vec![Series { name: "revenue".into(), rows: vec![row("2", "100")] }];
+        state.discounted_series = "revenue".into();
+        state.series[0].rows[0].discount = "1.2".into();
+        assert_eq!(state.implied_discount().err(), Some(GoalSeekError::RowDiscountRates { rows: vec![0] }));
+        state.series[0].rows[0].discount.clear();
+        assert!(state.implied_discount().is_ok());
+    }
+}
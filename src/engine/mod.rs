@@ -0,0 +1,411 @@
+//! Headless valuation engine.
+//!
+//! Everything needed to turn a scenario ([`StateData`]) into cash-flows, unit DCF values and
+//! a final DCF result lives here, so the valuation can be used without the egui front-end.
+
+use serde::{Deserialize, Serialize};
+
+mod bridge;
+mod calendar;
+mod cashflow;
+mod curve;
+mod discount;
+mod drivers;
+mod error;
+mod goalseek;
+mod montecarlo;
+mod parameters;
+mod scenario;
+mod sensitivity;
+mod series;
+mod terminal;
+mod tornado;
+mod wacc;
+
+pub use bridge::{BridgeInputs, EquityValue};
+pub use calendar::{CalendarError, CalendarInputs, Date, Frequency};
+pub use cashflow::{Cashflow, ModelKind, TimeBase};
+pub use curve::{interpolate, zero_rate, CurveError, CurveKind, CurvePoint, Interpolation, YieldCurve};
+pub use discount::{DiscountCurve, Timing, ValuationMode};
+pub use drivers::{Driver, DriverSeries, Drivers};
+pub use error::RowError;
+pub use goalseek::{GoalSeekError, GoalSeekInputs, Root};
+pub use montecarlo::{DistributionKind, MonteCarloError, MonteCarloInputs, MonteCarloRun, Simulation, Uncertainty};
+pub use parameters::{uses_variable, Parameter, ParameterError, RESERVED_NAMES};
+pub use scenario::{ExpectedValuation, Scenario, ScenarioResult};
+pub use sensitivity::{Assumption, SensitivityAxis, SensitivityInputs, SensitivityTable};
+pub use series::{NamedSeries, Series, SeriesError};
+pub use terminal::{TerminalInputs, TerminalMethod, TerminalValue, TerminalValueError, MAX_FADE_PERIODS};
+pub use tornado::{numeric_literals, RowSource, Tornado, TornadoBar, TornadoInput};
+pub use wacc::{Wacc, WaccInputs};
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Row {
+    /// End of Period
+    pub end: String,
+    pub expr: String,
+    /// How `expr` is evaluated; [`ModelKind::Auto`] decides from its variables.
+    pub kind: ModelKind,
+    /// Discount factor of this segment, constant or in `t`; empty uses [`StateData::discount`].
+    pub discount: String,
+}
+
+#[derive(Copy, Clone, Serialize)]
+pub struct DcfData {
+    pub cashflow: f64,
+    pub dcf_unit: f64,
+    pub dcf_sum: f64,
+}
+
+/// A whole scenario, exactly as it is saved to / loaded from JSON.
+///
+/// Fields missing from older files fall back to their [`Default`] values.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateData {
+    pub rows: Vec<Row>,
+    /// Named values the row expressions can refer to besides `t`, `T` and `y`.
+    pub parameters: Vec<Parameter>,
+    /// Named piecewise series the expressions can refer to as `name(T)`.
+    pub series: Vec<Series>,
+    /// Name of the series that is discounted; empty discounts `rows` (or the drivers).
+    pub discounted_series: String,
+    /// Named variants of `rows`, `discount` and `growth`; those fields hold the active one.
+    pub scenarios: Vec<Scenario>,
+    pub active_scenario: usize,
+    /// Driver-based free cash flow used instead of `rows` when enabled.
+    pub drivers: Drivers,
+    pub growth: String,
+    pub terminal: TerminalInputs,
+    pub discount: String,
+    /// How `discount` was derived, when built from its components.
+    pub wacc: WaccInputs,
+    /// Length of one period.
+    pub frequency: Frequency,
+    /// Dates of the periods; off keeps the bare period index.
+    pub calendar: CalendarInputs,
+    /// When within a period its cash-flow is assumed to arrive.
+    pub timing: Timing,
+    pub valuation_mode: ValuationMode,
+    pub yield_curve: YieldCurve,
+    pub ode_step_size: String,
+    /// Whether `t` restarts in every segment; `T` is absolute either way.
+    pub time_base: TimeBase,
+    pub use_log_scale: bool,
+    /// Enterprise-to-equity bridge applied to the DCF result.
+    pub bridge: BridgeInputs,
+    /// Ranges of the two-way sensitivity table.
+    pub sensitivity: SensitivityInputs,
+    /// Percentage every input is flexed by in the tornado chart.
+    pub tornado_flex: String,
+    /// Distributions of the uncertain assumptions.
+    pub monte_carlo: MonteCarloInputs,
+    /// Market value the implied rates are solved against.
+    pub goal_seek: GoalSeekInputs,
+    /// Add the terminal value to the result without discounting it, as versions before
+    /// the fix did. Only meant for reproducing numbers of old saved files.
+    pub legacy_terminal_value: bool,
+}
+
+impl Default for StateData {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            parameters: Vec::new(),
+            series: Vec::new(),
+            discounted_series: "".into(),
+            scenarios: Vec::new(),
+            active_scenario: 0,
+            drivers: Drivers::default(),
+            growth: "1.02".into(),
+            terminal: TerminalInputs::default(),
+            discount: "1.03".into(),
+            wacc: WaccInputs::default(),
+            frequency: Frequency::default(),
+            calendar: CalendarInputs::default(),
+            timing: Timing::default(),
+            valuation_mode: ValuationMode::default(),
+            yield_curve: YieldCurve::default(),
+            ode_step_size: "0.01".into(),
+            time_base: TimeBase::default(),
+            use_log_scale: false,
+            bridge: BridgeInputs::default(),
+            sensitivity: SensitivityInputs::default(),
+            tornado_flex: "10".into(),
+            monte_carlo: MonteCarloInputs::default(),
+            goal_seek: GoalSeekInputs::default(),
+            legacy_terminal_value: false,
+        }
+    }
+}
+
+/// Result of a full valuation run.
+#[derive(Clone, Serialize)]
+pub struct Valuation {
+    pub dcf: Vec<DcfData>,
+    /// Why the enabled yield curve was not used; the row rates discount instead.
+    pub curve_error: Option<CurveError>,
+    /// Date of each period, with the calendar on.
+    pub dates: Option<Vec<Date>>,
+    /// Why the dates could not be built; periods are then spaced evenly.
+    pub calendar_error: Option<CalendarError>,
+    #[serde(skip)]
+    pub discount_curve: DiscountCurve,
+    /// One entry per row, see [`Cashflow::row_errors`].
+    pub row_errors: Vec<Option<RowError>>,
+    /// Driver columns, in driver mode.
+    pub drivers: Option<DriverSeries>,
+    /// Every named series, see [`Cashflow::series`].
+    pub series: Vec<NamedSeries>,
+    pub series_errors: Vec<SeriesError>,
+    pub terminal_value: Result<TerminalValue, TerminalValueError>,
+    /// `None` while the terminal value is invalid.
+    pub total: Option<f64>,
+    /// Equity value and per-share value bridged from `total`.
+    pub equity: Option<EquityValue>,
+}
+
+impl StateData {
+    /// Sum of the discounted cash-flows plus the terminal value.
+    pub fn dcf_result(&self, dcf_data: &[DcfData], terminal_value: &TerminalValue) -> f64 {
+        dcf_data.last().map(|d| d.dcf_sum).unwrap_or(0.0)
+            + if self.legacy_terminal_value { terminal_value.value } else { terminal_value.pv }
+    }
+
+    /// Runs the whole pipeline: cash-flow, DCF table, terminal value and total.
+    ///
+    /// With the WACC builder enabled the built WACC is the discount rate, whatever `discount`
+    /// holds.
+    pub fn evaluate(&self) -> Valuation {
+        if let Some(discount) = self.wacc_discount().filter(|d| *d != self.discount) {
+            return StateData { discount, ..self.clone() }.evaluate();
+        }
+        let cashflow = self.calculate_cashflow();
+        let discount_curve = self.discount_curve(&cashflow);
+        let dcf = self.calculate_dcf(&discount_curve, &cashflow);
+        let terminal_value = self.terminal_value(&discount_curve, &dcf);
+        let total = terminal_value.as_ref().ok().map(|tv| self.dcf_result(&dcf, tv));
+        let equity = total.map(|total| self.equity_value(total));
+        let (dates, calendar_error) = match self.period_dates(dcf.len()) {
+            Ok(dates) => (dates, None),
+            Err(e) => (None, Some(e)),
+        };
+        Valuation {
+            dcf,
+            curve_error: self.yield_curve.error(),
+            dates,
+            calendar_error,
+            discount_curve,
+            row_errors: cashflow.row_errors,
+            drivers: cashflow.drivers,
+            series: cashflow.series,
+            series_errors: cashflow.series_errors,
+            terminal_value,
+            total,
+            equity,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9 * expected.abs().max(1.0), "{actual} != {expected}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn row(end: &str, expr: &str) -> Row {
+        Row { end: end.into(), expr: expr.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn constant_and_time_rows() {
+        let state = StateData { rows: vec![row("2", "100"), row("4", "100 * 1.1^t")], ..Default::default() };
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.row_errors, [None, None]);
+        assert_eq!(cashflow.values.len(), 5);
+        for (actual, expected) in cashflow.values.into_iter().zip([100.0, 100.0, 100.0, 110.0, 121.0]) {
+            assert_close(actual, expected);
+        }
+    }
+
+    #[test]
+    fn ode_row_continues_from_the_previous_value() {
+        let state = StateData { rows: vec![row("1", "100"), row("3", "0.1 * y")], ..Default::default() };
+        let cashflow = state.calculate_cashflow().values;
+        assert_eq!(cashflow.len(), 4);
+        for (t, actual) in cashflow.into_iter().enumerate().skip(1) {
+            // sampled at the solver step that reaches the period, so within a step of 0.01
+            let expected = 100.0 * (0.1 * (t as f64 - 1.0)).exp();
+            assert!((actual - expected).abs() < expected * 0.1 * 0.02, "{actual} != {expected}");
+        }
+    }
+
+    /// Error of the second row, after a valid first row ending at 1.
+    fn second_row_error(end: &str, expr: &str) -> Option<RowError> {
+        let state = StateData { rows: vec![row("1", "100"), row(end, expr)], ..Default::default() };
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.row_errors[0], None);
+        cashflow.row_errors[1].clone()
+    }
+
+    #[test]
+    fn invalid_period() {
+        assert_eq!(second_row_error("", "100"), Some(RowError::InvalidPeriod { end: "".into() }));
+        assert_eq!(second_row_error("2.5", "100"), Some(RowError::InvalidPeriod { end: "2.5".into() }));
+    }
+
+    #[test]
+    fn non_monotonic_period_is_skipped() {
+        let state = StateData { rows: vec![row("3", "100"), row("2", "50"), row("4", "200")], ..Default::default() };
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.row_errors, [None, Some(RowError::NonMonotonicPeriod { end: 2, previous: 3 }), None]);
+        assert_eq!(cashflow.values, [100.0, 100.0, 100.0, 100.0, 200.0]);
+    }
+
+    #[test]
+    fn unknown_variable_fills_the_segment_with_zero() {
+        let state = StateData { rows: vec![row("1", "100"), row("3", "100 * x")], ..Default::default() };
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.row_errors[1], Some(RowError::UnknownVariable { name: "x".into() }));
+        assert_eq!(cashflow.values, [100.0, 100.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert!(matches!(second_row_error("2", "100 *"), Some(RowError::Syntax { .. })));
+        assert!(matches!(second_row_error("2", "100 ) 2"), Some(RowError::Syntax { position: Some(4), .. })));
+    }
+
+    #[test]
+    fn function_arguments() {
+        assert!(matches!(
+            second_row_error("2", "sqrt(1, 2)"),
+            Some(RowError::Function { name, .. }) if name == "sqrt"
+        ));
+    }
+
+    #[test]
+    fn invalid_discount_falls_back_to_the_global_rate() {
+        let mut state = StateData { rows: vec![row("1", "100"), row("3", "100")], ..Default::default() };
+        state.rows[1].discount = "1.1 * x".into();
+        let cashflow = state.calculate_cashflow();
+        assert!(matches!(&cashflow.row_errors[1], Some(RowError::Discount { .. })));
+        assert_eq!(cashflow.values, [100.0; 4]);
+        assert_eq!(cashflow.discount_rates, [1.0, 1.03, 1.03, 1.03]);
+    }
+
+    #[test]
+    fn solver_failure() {
+        // blows up at t = 1, the solver runs out of steps
+        assert!(matches!(second_row_error("3", "y * y / 100"), Some(RowError::Solver { .. })));
+    }
+
+    #[test]
+    fn legacy_terminal_value_reproduces_the_app() {
+        let state = StateData { rows: vec![row("3", "100")], legacy_terminal_value: true, ..Default::default() };
+        let valuation = state.evaluate();
+        // `discount ^ t` per period, then Gordon growth on the last cash-flow, added undiscounted
+        let sum: f64 = (0..=3).map(|t| 100.0 / 1.03f64.powi(t)).sum();
+        let terminal = 100.0 * 1.02 / (1.03 - 1.02);
+        assert_close(valuation.dcf[3].dcf_sum, sum);
+        assert_close(valuation.terminal_value.unwrap().value, terminal);
+        assert_close(valuation.total.unwrap(), sum + terminal);
+    }
+
+    #[test]
+    fn terminal_value_is_discounted_from_the_last_period() {
+        let state = StateData { rows: vec![row("3", "100")], ..Default::default() };
+        let valuation = state.evaluate();
+        let sum: f64 = (0..=3).map(|t| 100.0 / 1.03f64.powi(t)).sum();
+        let terminal = 100.0 * 1.02 / (1.03 - 1.02);
+        let tv = valuation.terminal_value.unwrap();
+        assert_close(tv.value, terminal);
+        assert_close(tv.pv, terminal / 1.03f64.powi(3));
+        assert_close(valuation.total.unwrap(), sum + terminal / 1.03f64.powi(3));
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let state: StateData = serde_json::from_str(r#"{"rows":[{"end":"3","expr":"100"}]}"#).unwrap();
+        assert_eq!(state.discount, "1.03");
+        assert!(!state.legacy_terminal_value);
+        assert_eq!(state.rows[0].kind, ModelKind::Auto);
+    }
+
+    #[test]
+    fn model_kind_is_detected_from_the_variables() {
+        let kind = |expr: &str| row("1", expr).model_kind();
+        assert_eq!(kind("100"), Some(ModelKind::Constant));
+        // a function call is not a variable
+        assert_eq!(kind("sqrt(2)"), Some(ModelKind::Constant));
+        assert_eq!(kind("100 * exp(0.1 * t)"), Some(ModelKind::TimeFunction));
+        assert_eq!(kind("0.1 * y + t"), Some(ModelKind::Ode));
+        assert_eq!(kind("100 *"), None);
+        let explicit = Row { kind: ModelKind::Ode, ..row("1", "sqrt(2)") };
+        assert_eq!(explicit.model_kind(), Some(ModelKind::Ode));
+    }
+
+    #[test]
+    fn explicit_model_kind_overrides_the_detection() {
+        let rows = |kind: ModelKind| vec![row("1", "100"), Row { kind, ..row("3", "10") }];
+        let values = |kind| {
+            StateData { rows: rows(kind), ode_step_size: "1".into(), ..Default::default() }.calculate_cashflow().values
+        };
+        assert_eq!(values(ModelKind::Auto), [100.0, 100.0, 10.0, 10.0]);
+        // a constant rate of change from the previous value
+        let ode = values(ModelKind::Ode);
+        for (actual, expected) in ode.into_iter().zip([100.0, 100.0, 110.0, 120.0]) {
+            assert_close(actual, expected);
+        }
+        // forcing a constant on an expression in `t` fails like any unknown variable
+        let state = StateData { rows: vec![Row { kind: ModelKind::Constant, ..row("1", "t") }], ..Default::default() };
+        assert_eq!(state.calculate_cashflow().row_errors, [Some(RowError::UnknownVariable { name: "t".into() })]);
+    }
+
+    #[test]
+    fn local_and_absolute_time() {
+        let mut state = StateData { rows: vec![row("1", "t"), row("3", "10 * t + T")], ..Default::default() };
+        assert_eq!(state.calculate_cashflow().values, [0.0, 1.0, 12.0, 23.0]);
+        assert_eq!(row("1", "T").model_kind(), Some(ModelKind::TimeFunction));
+
+        state.time_base = TimeBase::Absolute;
+        assert_eq!(state.calculate_cashflow().values, [0.0, 1.0, 22.0, 33.0]);
+
+        // the rate of change is `T`, so y grows by T^2 / 2 whichever time `t` counts
+        for time_base in TimeBase::ALL {
+            let state = StateData {
+                rows: vec![row("2", "0"), row("4", "T + 0 * y")],
+                time_base,
+                ode_step_size: "0.5".into(),
+                ..Default::default()
+            };
+            let values = state.calculate_cashflow().values;
+            assert_close(values[4], (16.0 - 4.0) / 2.0);
+        }
+    }
+
+    #[test]
+    fn series_are_read_at_absolute_periods() {
+        let mut state = StateData {
+            rows: vec![row("2", "revenue(t)"), row("4", "revenue(T)")],
+            series: vec![Series { name: "revenue".into(), rows: vec![row("4", "10 * T")] }],
+            ..Default::default()
+        };
+        assert_eq!(state.calculate_cashflow().values, [0.0, 10.0, 20.0, 30.0, 40.0]);
+
+        state.rows[1].expr = "revenue(t)".into();
+        let error = state.calculate_cashflow().row_errors[1].clone().unwrap();
+        assert_eq!(error, RowError::LocalSeriesTime { name: "revenue".into(), start: 2 });
+        assert!(error.to_string().contains("write `revenue(T)`"));
+
+        // with absolute time `t` is the period the series takes
+        state.time_base = TimeBase::Absolute;
+        let cashflow = state.calculate_cashflow();
+        assert!(cashflow.row_errors.iter().all(Option::is_none));
+        assert_eq!(cashflow.values, [0.0, 10.0, 20.0, 30.0, 40.0]);
+    }
+}
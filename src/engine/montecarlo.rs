@@ -0,0 +1,464 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Assumption, StateData};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionKind {
+    /// `mean`, `std_dev`
+    #[default]
+    Normal,
+    /// `mean`, `std_dev` of the value itself (not of its logarithm); positive values only.
+    LogNormal,
+    /// `min`, `max`
+    Uniform,
+    /// `min`, `mode`, `max`
+    Triangular,
+    /// Beta-PERT over `min`, `mode`, `max`
+    Pert,
+}
+
+impl DistributionKind {
+    pub const ALL: [DistributionKind; 5] = [
+        DistributionKind::Normal,
+        DistributionKind::LogNormal,
+        DistributionKind::Uniform,
+        DistributionKind::Triangular,
+        DistributionKind::Pert,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DistributionKind::Normal => "Normal",
+            DistributionKind::LogNormal => "Lognormal",
+            DistributionKind::Uniform => "Uniform",
+            DistributionKind::Triangular => "Triangular",
+            DistributionKind::Pert => "PERT",
+        }
+    }
+
+    /// Whether the distribution is given by `mean` / `std_dev` rather than `min` / `mode` / `max`.
+    pub fn is_moment_based(&self) -> bool {
+        matches!(self, DistributionKind::Normal | DistributionKind::LogNormal)
+    }
+}
+
+/// Probability distribution attached to one assumption; only the fields of its kind are used.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Uncertainty {
+    pub assumption: Assumption,
+    pub kind: DistributionKind,
+    pub mean: String,
+    pub std_dev: String,
+    pub min: String,
+    pub mode: String,
+    pub max: String,
+}
+
+impl Default for Uncertainty {
+    fn default() -> Self {
+        Self {
+            assumption: Assumption::Discount,
+            kind: DistributionKind::Normal,
+            mean: "1.08".into(),
+            std_dev: "0.01".into(),
+            min: "1.06".into(),
+            mode: "1.08".into(),
+            max: "1.10".into(),
+        }
+    }
+}
+
+/// Settings of a Monte Carlo run.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonteCarloInputs {
+    pub runs: String,
+    /// Same seed, same results.
+    pub seed: String,
+    pub uncertainties: Vec<Uncertainty>,
+}
+
+impl Default for MonteCarloInputs {
+    fn default() -> Self {
+        Self { runs: "1000".into(), seed: "42".into(), uncertainties: Vec::new() }
+    }
+}
+
+impl MonteCarloInputs {
+    /// Most runs a simulation does.
+    pub const MAX_RUNS: usize = 100_000;
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MonteCarloError {
+    /// `runs` is not a positive integer up to [`MonteCarloInputs::MAX_RUNS`].
+    InvalidRuns { runs: String },
+    InvalidSeed { seed: String },
+    /// The parameters of the distribution at `index` do not parse or do not describe a
+    /// distribution (e.g. `min > max`, negative `std_dev`).
+    InvalidDistribution { index: usize, assumption: Assumption },
+    /// The distribution at `index` is attached to a parameter that does not exist.
+    UnknownParameter { index: usize, name: String },
+    /// Every run ended with invalid assumptions.
+    NoValidRuns,
+}
+
+impl fmt::Display for MonteCarloError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MonteCarloError::InvalidRuns { runs } => {
+                write!(f, "Number of runs `{runs}` must be between 1 and {}", MonteCarloInputs::MAX_RUNS)
+            },
+            MonteCarloError::InvalidSeed { seed } => write!(f, "Invalid seed `{seed}`"),
+            MonteCarloError::InvalidDistribution { index, assumption } => {
+                write!(f, "Distribution {} ({}) has invalid parameters", index + 1, assumption.label())
+            },
+            MonteCarloError::UnknownParameter { index, name } => {
+                write!(f, "Distribution {} is attached to an unknown parameter `{name}`", index + 1)
+            },
+            MonteCarloError::NoValidRuns => write!(f, "No run produced a valid DCF result"),
+        }
+    }
+}
+
+impl std::error::Error for MonteCarloError {}
+
+/// SplitMix64; small, seedable and the same on every target.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `(0, 1]`, so it is safe to take the logarithm.
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, Box-Muller.
+    fn normal(&mut self) -> f64 {
+        (-2.0 * self.uniform().ln()).sqrt() * (std::f64::consts::TAU * self.uniform()).cos()
+    }
+
+    /// Gamma with shape `alpha >= 1` and unit scale, Marsaglia-Tsang.
+    fn gamma(&mut self, alpha: f64) -> f64 {
+        let d = alpha - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.normal();
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            if self.uniform().ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+}
+
+/// Parsed and checked distribution.
+#[derive(Copy, Clone)]
+enum Sampler {
+    Normal { mean: f64, std_dev: f64 },
+    LogNormal { mu: f64, sigma: f64 },
+    Uniform { min: f64, max: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+    Pert { min: f64, max: f64, alpha: f64, beta: f64 },
+}
+
+impl Uncertainty {
+    fn sampler(&self) -> Option<Sampler> {
+        let mean = || self.mean.parse::<f64>().ok();
+        let std_dev = || self.std_dev.parse::<f64>().ok().filter(|&s| s >= 0.0);
+        let range = || {
+            let (min, max) = (self.min.parse::<f64>().ok()?, self.max.parse::<f64>().ok()?);
+            (min <= max).then_some((min, max))
+        };
+        let mode = |min: f64, max: f64| self.mode.parse::<f64>().ok().filter(|&m| min <= m && m <= max);
+
+        Some(match self.kind {
+            DistributionKind::Normal => Sampler::Normal { mean: mean()?, std_dev: std_dev()? },
+            DistributionKind::LogNormal => {
+                let (mean, std_dev) = (mean().filter(|&m| m > 0.0)?, std_dev()?);
+                let sigma2 = (1.0 + (std_dev / mean).powi(2)).ln();
+                Sampler::LogNormal { mu: mean.ln() - sigma2 / 2.0, sigma: sigma2.sqrt() }
+            },
+            DistributionKind::Uniform => {
+                let (min, max) = range()?;
+                Sampler::Uniform { min, max }
+            },
+            DistributionKind::Triangular => {
+                let (min, max) = range()?;
+                Sampler::Triangular { min, mode: mode(min, max)?, max }
+            },
+            DistributionKind::Pert => {
+                let (min, max) = range()?;
+                let mode = mode(min, max)?;
+                let (alpha, beta) = if max > min {
+                    (1.0 + 4.0 * (mode - min) / (max - min), 1.0 + 4.0 * (max - mode) / (max - min))
+                } else {
+                    (1.0, 1.0)
+                };
+                Sampler::Pert { min, max, alpha, beta }
+            },
+        })
+    }
+}
+
+impl Sampler {
+    fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Sampler::Normal { mean, std_dev } => mean + std_dev * rng.normal(),
+            Sampler::LogNormal { mu, sigma } => (mu + sigma * rng.normal()).exp(),
+            Sampler::Uniform { min, max } => min + (max - min) * rng.uniform(),
+            Sampler::Triangular { min, mode, max } => {
+                let u = rng.uniform();
+                if max == min {
+                    min
+                } else if u < (mode - min) / (max - min) {
+                    min + (u * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+                }
+            },
+            Sampler::Pert { min, max, alpha, beta } => {
+                let x = rng.gamma(alpha);
+                let y = rng.gamma(beta);
+                min + (max - min) * x / (x + y)
+            },
+        }
+    }
+}
+
+/// DCF results of a Monte Carlo run.
+#[derive(Clone, Debug, Serialize)]
+pub struct Simulation {
+    /// Valid results, sorted ascending.
+    pub results: Vec<f64>,
+    /// Runs whose sampled assumptions gave no valid result.
+    pub invalid: usize,
+    pub mean: f64,
+}
+
+impl Simulation {
+    /// `p`-th percentile (`0..=100`), linearly interpolated.
+    pub fn percentile(&self, p: f64) -> f64 {
+        let rank = (p / 100.0).clamp(0.0, 1.0) * (self.results.len() - 1) as f64;
+        let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+        self.results[lo] + (self.results[hi] - self.results[lo]) * (rank - lo as f64)
+    }
+
+    pub fn median(&self) -> f64 {
+        self.percentile(50.0)
+    }
+
+    /// `(bin center, count)` of `bins` equally wide bins between the smallest and largest result.
+    pub fn histogram(&self, bins: usize) -> Vec<(f64, usize)> {
+        let (lo, hi) = (self.results[0], self.results[self.results.len() - 1]);
+        let width = if hi > lo { (hi - lo) / bins as f64 } else { 1.0 };
+        let mut counts = vec![0; bins];
+        for &v in &self.results {
+            counts[(((v - lo) / width) as usize).min(bins - 1)] += 1;
+        }
+        counts.into_iter().enumerate().map(|(i, n)| (lo + width * (i as f64 + 0.5), n)).collect()
+    }
+}
+
+/// Monte Carlo simulation in progress, advanced a chunk of runs at a time so a front-end can
+/// show its progress and stay responsive.
+pub struct MonteCarloRun {
+    state: StateData,
+    samplers: Vec<(Assumption, Sampler)>,
+    rng: Rng,
+    runs: usize,
+    done: usize,
+    results: Vec<f64>,
+}
+
+impl MonteCarloRun {
+    /// Does up to `n` more runs; returns `true` once all runs are done.
+    pub fn step(&mut self, n: usize) -> bool {
+        for _ in 0..n.min(self.runs - self.done) {
+            for (assumption, sampler) in &self.samplers {
+                if let Some(value) = self.state.assumption_mut(assumption) {
+                    *value = sampler.sample(&mut self.rng).to_string();
+                }
+            }
+            if let Some(total) = self.state.evaluate().total.filter(|v| v.is_finite()) {
+                self.results.push(total);
+            }
+            self.done += 1;
+        }
+        self.is_done()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done == self.runs
+    }
+
+    /// Fraction of the runs done, `0..=1`.
+    pub fn progress(&self) -> f64 {
+        self.done as f64 / self.runs as f64
+    }
+
+    /// Statistics of the runs done so far.
+    pub fn finish(self) -> Result<Simulation, MonteCarloError> {
+        let mut results = self.results;
+        if results.is_empty() {
+            return Err(MonteCarloError::NoValidRuns);
+        }
+
+        results.sort_by(f64::total_cmp);
+        let mean = results.iter().sum::<f64>() / results.len() as f64;
+        Ok(Simulation { invalid: self.done - results.len(), results, mean })
+    }
+}
+
+impl StateData {
+    /// Checks the Monte Carlo settings and prepares a run; nothing is evaluated until
+    /// [`MonteCarloRun::step`].
+    pub fn start_monte_carlo(&self) -> Result<MonteCarloRun, MonteCarloError> {
+        let inputs = &self.monte_carlo;
+        let runs = match inputs.runs.parse::<usize>() {
+            Ok(n) if (1..=MonteCarloInputs::MAX_RUNS).contains(&n) => n,
+            _ => return Err(MonteCarloError::InvalidRuns { runs: inputs.runs.clone() }),
+        };
+        let seed = inputs.seed.parse::<u64>().map_err(|_| MonteCarloError::InvalidSeed { seed: inputs.seed.clone() })?;
+        let samplers = inputs.uncertainties.iter()
+            .enumerate()
+            .map(|(index, u)| {
+                if let Assumption::Parameter(name) = &u.assumption {
+                    if !self.assumptions().contains(&u.assumption) {
+                        return Err(MonteCarloError::UnknownParameter { index, name: name.clone() });
+                    }
+                }
+                u.sampler()
+                    .map(|s| (u.assumption.clone(), s))
+                    .ok_or_else(|| MonteCarloError::InvalidDistribution { index, assumption: u.assumption.clone() })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MonteCarloRun {
+            state: self.clone(),
+            samplers,
+            rng: Rng(seed),
+            runs,
+            done: 0,
+            results: Vec::with_capacity(runs),
+        })
+    }
+
+    /// Runs the whole valuation `runs` times with every [`Uncertainty`] drawn from its
+    /// distribution; assumptions without one keep their value.
+    pub fn monte_carlo(&self) -> Result<Simulation, MonteCarloError> {
+        let mut run = self.start_monte_carlo()?;
+        run.step(usize::MAX);
+        run.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row, Parameter};
+
+    fn scenario(uncertainties: Vec<Uncertainty>) -> StateData {
+        StateData {
+            rows: vec![row("3", "100")],
+            discount: "1.08".into(),
+            monte_carlo: MonteCarloInputs { runs: "500".into(), seed: "7".into(), uncertainties },
+            ..Default::default()
+        }
+    }
+
+    fn uniform(assumption: Assumption, min: &str, max: &str) -> Uncertainty {
+        Uncertainty {
+            assumption,
+            kind: DistributionKind::Uniform,
+            min: min.into(),
+            max: max.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn same_seed_same_results() {
+        let state = scenario(vec![Uncertainty::default(), uniform(Assumption::Growth, "1.00", "1.04")]);
+        let first = state.monte_carlo().unwrap();
+        assert_eq!(first.results.len() + first.invalid, 500);
+        assert_eq!(first.results, state.monte_carlo().unwrap().results);
+
+        // stepping in chunks draws the same numbers
+        let mut run = state.start_monte_carlo().unwrap();
+        while !run.step(64) {
+            assert!(run.progress() < 1.0);
+        }
+        assert_eq!(run.finish().unwrap().results, first.results);
+
+        let mut other = state.clone();
+        other.monte_carlo.seed = "8".into();
+        assert_ne!(other.monte_carlo().unwrap().results, first.results);
+    }
+
+    #[test]
+    fn percentiles_interpolate_between_results() {
+        let sim = Simulation { results: vec![1.0, 2.0, 3.0, 4.0, 5.0], invalid: 0, mean: 3.0 };
+        assert_close(sim.percentile(0.0), 1.0);
+        assert_close(sim.median(), 3.0);
+        assert_close(sim.percentile(10.0), 1.4);
+        assert_close(sim.percentile(100.0), 5.0);
+        assert_eq!(sim.histogram(2), [(2.0, 2), (4.0, 3)]);
+    }
+
+    #[test]
+    fn uniform_results_stay_in_range() {
+        let state = scenario(vec![uniform(Assumption::Growth, "1.00", "1.04")]);
+        let sim = state.monte_carlo().unwrap();
+        let at = |growth: &str| StateData { growth: growth.into(), ..state.clone() }.evaluate().total.unwrap();
+        assert_eq!(sim.invalid, 0);
+        assert!(at("1.00") <= sim.percentile(0.0) && sim.percentile(100.0) <= at("1.04"));
+        assert!(sim.percentile(5.0) < sim.median() && sim.median() < sim.percentile(95.0));
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let mut state = scenario(vec![uniform(Assumption::Growth, "1.04", "1.00")]);
+        assert_eq!(
+            state.monte_carlo().err(),
+            Some(MonteCarloError::InvalidDistribution { index: 0, assumption: Assumption::Growth })
+        );
+        state.monte_carlo.runs = "0".into();
+        assert_eq!(state.monte_carlo().err(), Some(MonteCarloError::InvalidRuns { runs: "0".into() }));
+        // growth always above the discount rate
+        let state = scenario(vec![uniform(Assumption::Growth, "1.10", "1.20")]);
+        assert_eq!(state.monte_carlo().err(), Some(MonteCarloError::NoValidRuns));
+        let state = scenario(vec![uniform(Assumption::Parameter("g".into()), "1.00", "1.04")]);
+        assert_eq!(
+            state.monte_carlo().err(),
+            Some(MonteCarloError::UnknownParameter { index: 0, name: "g".into() })
+        );
+    }
+
+    #[test]
+    fn parameters_are_sampled() {
+        let mut state = scenario(vec![uniform(Assumption::Parameter("base".into()), "50", "150")]);
+        state.rows = vec![row("3", "base")];
+        state.parameters = vec![Parameter { name: "base".into(), value: "100".into(), ..Default::default() }];
+        let sim = state.monte_carlo().unwrap();
+        let at = |base: f64| state.evaluate().total.unwrap() * base / 100.0;
+        assert_eq!(sim.invalid, 0);
+        assert!(at(50.0) <= sim.percentile(0.0) && sim.percentile(100.0) <= at(150.0));
+        assert!(sim.percentile(0.0) < sim.percentile(100.0));
+    }
+}
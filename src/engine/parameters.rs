@@ -0,0 +1,143 @@
+use std::fmt;
+
+use meval::{Context, Expr, tokenizer::Token};
+use serde::{Deserialize, Serialize};
+
+use super::StateData;
+
+/// Named value usable in every row expression, e.g. `g1` in `base * (1 + g1)^t`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Parameter {
+    pub name: String,
+    pub value: String,
+    pub description: String,
+}
+
+/// Why a [`Parameter`] is left out of the expressions.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ParameterError {
+    /// Not a letter or `_` followed by letters, digits or `_`.
+    InvalidName { name: String },
+    /// `t`, `T` and `y` are the variables of the model.
+    ReservedName { name: String },
+    /// An earlier parameter has the same name.
+    DuplicateName { name: String },
+    InvalidValue { value: String },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterError::InvalidName { name } if name.is_empty() => write!(f, "Name is missing"),
+            ParameterError::InvalidName { name } => write!(f, "Invalid name `{name}`"),
+            ParameterError::ReservedName { name } => write!(f, "`{name}` is a variable of the model"),
+            ParameterError::DuplicateName { name } => write!(f, "`{name}` is defined twice"),
+            ParameterError::InvalidValue { value } => write!(f, "Invalid value `{value}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+/// Names the model binds itself; parameters cannot take them.
+pub const RESERVED_NAMES: [&str; 3] = ["t", "T", "y"];
+
+/// A letter or `_` followed by letters, digits or `_`.
+pub(super) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether the parsed expression refers to the variable `name`.
+pub fn uses_variable(expr: &Expr, name: &str) -> bool {
+    expr.iter().any(|token| matches!(token, Token::Var(v) if v == name))
+}
+
+impl StateData {
+    /// One diagnostic per parameter, `None` when it is usable.
+    pub fn parameter_errors(&self) -> Vec<Option<ParameterError>> {
+        self.parameters.iter()
+            .enumerate()
+            .map(|(i, p)| {
+                if !is_identifier(&p.name) {
+                    Some(ParameterError::InvalidName { name: p.name.clone() })
+                } else if RESERVED_NAMES.contains(&p.name.as_str()) {
+                    Some(ParameterError::ReservedName { name: p.name.clone() })
+                } else if self.parameters[..i].iter().any(|q| q.name == p.name) {
+                    Some(ParameterError::DuplicateName { name: p.name.clone() })
+                } else if p.value.trim().parse::<f64>().is_err() {
+                    Some(ParameterError::InvalidValue { value: p.value.clone() })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Built-in constants and functions plus every valid parameter.
+    pub fn context(&self) -> Context<'static> {
+        let mut ctx = Context::new();
+        for (p, err) in self.parameters.iter().zip(self.parameter_errors()) {
+            if err.is_none() {
+                ctx.var(p.name.clone(), p.value.trim().parse().unwrap());
+            }
+        }
+        ctx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row};
+
+    fn parameter(name: &str, value: &str) -> Parameter {
+        Parameter { name: name.into(), value: value.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn parameter_errors() {
+        let state = StateData {
+            parameters: vec![
+                parameter("g1", "0.05"),
+                parameter("", "1"),
+                parameter("1g", "1"),
+                parameter("y", "1"),
+                parameter("g1", "0.06"),
+                parameter("_base", "1e2"),
+                parameter("base", "x"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(state.parameter_errors(), [
+            None,
+            Some(ParameterError::InvalidName { name: "".into() }),
+            Some(ParameterError::InvalidName { name: "1g".into() }),
+            Some(ParameterError::ReservedName { name: "y".into() }),
+            Some(ParameterError::DuplicateName { name: "g1".into() }),
+            None,
+            Some(ParameterError::InvalidValue { value: "x".into() }),
+        ]);
+        assert_eq!(ParameterError::InvalidName { name: "".into() }.to_string(), "Name is missing");
+    }
+
+    #[test]
+    fn expressions_see_the_valid_parameters() {
+        let state = StateData {
+            rows: vec![row("1", "base * (1 + g1)^t")],
+            parameters: vec![parameter("base", " 100 "), parameter("g1", "0.05"), parameter("g1", "0.5")],
+            ..Default::default()
+        };
+        let valuation = state.evaluate();
+        assert!(valuation.row_errors.iter().all(Option::is_none));
+        // the duplicate is left out
+        assert_close(valuation.dcf[1].cashflow, 105.0);
+
+        let expr: Expr = "base * t".parse().unwrap();
+        assert!(uses_variable(&expr, "t"));
+        assert!(!uses_variable(&expr, "y"));
+    }
+}
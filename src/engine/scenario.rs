@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Row, StateData};
+
+/// Named variant of the rows, discount and growth of a document.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    /// Weight of the scenario; weights are normalized, so `50`/`30`/`20` and `0.5`/`0.3`/`0.2` agree.
+    pub probability: String,
+    pub rows: Vec<Row>,
+    pub discount: String,
+    pub growth: String,
+}
+
+/// Result of one scenario in an [`ExpectedValuation`].
+#[derive(Clone, Serialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    /// Normalized probability
+    pub probability: f64,
+    pub cashflow: Vec<f64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ExpectedValuation {
+    pub scenarios: Vec<ScenarioResult>,
+    /// Probability-weighted DCF result; `None` when a weighted scenario is invalid or the
+    /// weights do not sum to a positive number.
+    pub expected: Option<f64>,
+}
+
+impl StateData {
+    /// Copies the live rows, discount and growth back into the active scenario.
+    pub fn store_scenario(&mut self) {
+        if let Some(scenario) = self.scenarios.get_mut(self.active_scenario) {
+            scenario.rows = self.rows.clone();
+            scenario.discount = self.discount.clone();
+            scenario.growth = self.growth.clone();
+        }
+    }
+
+    /// Makes scenario `index` the live one, keeping the edits of the current one.
+    pub fn switch_scenario(&mut self, index: usize) {
+        if index >= self.scenarios.len() {
+            return;
+        }
+        self.store_scenario();
+        self.active_scenario = index;
+        let scenario = &self.scenarios[index];
+        self.rows = scenario.rows.clone();
+        self.discount = scenario.discount.clone();
+        self.growth = scenario.growth.clone();
+    }
+
+    /// Adds a copy of the live scenario and switches to it. The first call also turns the
+    /// current document into a "Base" scenario.
+    pub fn add_scenario(&mut self) {
+        let live = |name: String, probability: &str| Scenario {
+            name,
+            probability: probability.into(),
+            rows: self.rows.clone(),
+            discount: self.discount.clone(),
+            growth: self.growth.clone(),
+        };
+        if self.scenarios.is_empty() {
+            self.scenarios.push(live("Base".into(), "1"));
+            self.active_scenario = 0;
+        }
+        let scenario = live(format!("Scenario {}", self.scenarios.len() + 1), "1");
+        self.store_scenario();
+        self.scenarios.push(scenario);
+        self.active_scenario = self.scenarios.len() - 1;
+    }
+
+    /// Removes the active scenario and switches to the one before it.
+    pub fn remove_scenario(&mut self) {
+        if self.active_scenario >= self.scenarios.len() {
+            return;
+        }
+        self.scenarios.remove(self.active_scenario);
+        if self.scenarios.is_empty() {
+            self.active_scenario = 0;
+            return;
+        }
+        let index = self.active_scenario.saturating_sub(1);
+        // the removed scenario must not be stored over its neighbour
+        self.active_scenario = usize::MAX;
+        self.switch_scenario(index);
+    }
+
+    /// The document as it is with scenario `index` live.
+    pub fn scenario_state(&self, index: usize) -> StateData {
+        let mut state = self.clone();
+        if index != self.active_scenario {
+            if let Some(scenario) = self.scenarios.get(index) {
+                state.rows = scenario.rows.clone();
+                state.discount = scenario.discount.clone();
+                state.growth = scenario.growth.clone();
+            }
+        }
+        state
+    }
+
+    /// Values every scenario and weights the DCF results by their probabilities.
+    pub fn expected_valuation(&self) -> ExpectedValuation {
+        let weights: Vec<f64> = self.scenarios.iter()
+            .map(|s| s.probability.parse::<f64>().ok().filter(|&p| p >= 0.0).unwrap_or(0.0))
+            .collect();
+        let sum: f64 = weights.iter().sum();
+
+        let scenarios: Vec<ScenarioResult> = self.scenarios.iter()
+            .zip(&weights)
+            .enumerate()
+            .map(|(i, (s, &w))| {
+                let valuation = self.scenario_state(i).evaluate();
+                ScenarioResult {
+                    name: s.name.clone(),
+                    probability: if sum > 0.0 { w / sum } else { 0.0 },
+                    cashflow: valuation.dcf.iter().map(|d| d.cashflow).collect(),
+                    total: valuation.total,
+                }
+            })
+            .collect();
+
+        let expected = if sum > 0.0 {
+            scenarios.iter()
+                .filter(|s| s.probability > 0.0)
+                .map(|s| s.total.map(|total| total * s.probability))
+                .sum()
+        } else {
+            None
+        };
+
+        ExpectedValuation { scenarios, expected }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row};
+
+    fn expressions(state: &StateData) -> Vec<&str> {
+        state.rows.iter().map(|r| r.expr.as_str()).collect()
+    }
+
+    /// "Base" with rows `100`, "Scenario 2" live with rows `200`.
+    fn two_scenarios() -> StateData {
+        let mut state = StateData { rows: vec![row("2", "100")], ..Default::default() };
+        state.add_scenario();
+        state.rows[0].expr = "200".into();
+        state
+    }
+
+    #[test]
+    fn add_and_switch_keep_the_edits() {
+        let mut state = two_scenarios();
+        assert_eq!(state.scenarios.len(), 2);
+        assert_eq!(state.scenarios[0].name, "Base");
+        assert_eq!(state.active_scenario, 1);
+
+        state.switch_scenario(0);
+        assert_eq!(expressions(&state), ["100"]);
+        assert_eq!(expressions(&state.scenario_state(1)), ["200"]);
+        state.discount = "1.05".into();
+
+        state.switch_scenario(1);
+        assert_eq!(expressions(&state), ["200"]);
+        assert_eq!(state.discount, "1.03");
+        assert_eq!(state.scenarios[0].discount, "1.05");
+        // out of range is ignored
+        state.switch_scenario(5);
+        assert_eq!(state.active_scenario, 1);
+    }
+
+    #[test]
+    fn store_copies_the_live_inputs() {
+        let mut state = two_scenarios();
+        assert_eq!(state.scenarios[1].rows[0].expr, "100");
+        state.store_scenario();
+        assert_eq!(state.scenarios[1].rows[0].expr, "200");
+    }
+
+    #[test]
+    fn remove_switches_to_the_previous_scenario() {
+        let mut state = two_scenarios();
+        state.add_scenario();
+        state.rows[0].expr = "300".into();
+        state.switch_scenario(1);
+        state.remove_scenario();
+        assert_eq!(state.active_scenario, 0);
+        assert_eq!(expressions(&state), ["100"]);
+        let names: Vec<&str> = state.scenarios.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Base", "Scenario 3"]);
+        assert_eq!(state.scenarios[1].rows[0].expr, "300");
+
+        state.remove_scenario();
+        state.remove_scenario();
+        assert!(state.scenarios.is_empty());
+        assert_eq!(state.active_scenario, 0);
+    }
+
+    #[test]
+    fn expected_valuation_weights_the_results() {
+        let mut state = two_scenarios();
+        state.scenarios[0].probability = "30".into();
+        state.scenarios[1].probability = "10".into();
+        let total = |i| state.scenario_state(i).evaluate().total.unwrap();
+        let expected = state.expected_valuation();
+        assert_close(expected.scenarios[0].probability, 0.75);
+        assert_close(expected.scenarios[1].total.unwrap(), 2.0 * total(0));
+        assert_close(expected.expected.unwrap(), 0.75 * total(0) + 0.25 * total(1));
+    }
+
+    #[test]
+    fn invalid_weighted_scenarios_invalidate_the_expectation() {
+        let mut state = two_scenarios();
+        state.growth = "1.05".into();
+        assert!(state.expected_valuation().expected.is_none());
+        // unless they have no weight
+        state.scenarios[1].probability = "0".into();
+        assert!(state.expected_valuation().expected.is_some());
+        state.scenarios[0].probability = "-1".into();
+        assert!(state.expected_valuation().expected.is_none());
+    }
+}
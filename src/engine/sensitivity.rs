@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use super::StateData;
+
+/// Numeric input of a scenario that sensitivities can flex.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Assumption {
+    #[default]
+    Discount,
+    Growth,
+    ExitMultiple,
+    HModelGrowth,
+    FadeGrowth,
+    OdeStepSize,
+    /// Value of the [`Parameter`](super::Parameter) with this name.
+    Parameter(String),
+}
+
+impl Assumption {
+    /// The assumptions every scenario has; see [`StateData::assumptions`] for the parameters.
+    pub const ALL: [Assumption; 6] = [
+        Assumption::Discount,
+        Assumption::Growth,
+        Assumption::ExitMultiple,
+        Assumption::HModelGrowth,
+        Assumption::FadeGrowth,
+        Assumption::OdeStepSize,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            Assumption::Discount => "Discount Rate".into(),
+            Assumption::Growth => "Growth".into(),
+            Assumption::ExitMultiple => "Exit Multiple".into(),
+            Assumption::HModelGrowth => "H-Model Initial Growth".into(),
+            Assumption::FadeGrowth => "Fade Initial Growth".into(),
+            Assumption::OdeStepSize => "ODE Step Size".into(),
+            Assumption::Parameter(name) => format!("Parameter {name}"),
+        }
+    }
+}
+
+/// Range of values one side of the table runs over.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensitivityAxis {
+    pub assumption: Assumption,
+    pub from: String,
+    pub to: String,
+    /// Number of values from `from` to `to`, both included.
+    pub steps: String,
+}
+
+impl SensitivityAxis {
+    /// Most values an axis runs over, to keep the grid responsive.
+    pub const MAX_STEPS: usize = 25;
+
+    /// Evenly spaced values; empty when a bound does not parse.
+    pub fn values(&self) -> Vec<f64> {
+        let (Ok(from), Ok(to)) = (self.from.parse::<f64>(), self.to.parse::<f64>()) else {
+            return Vec::new();
+        };
+        match self.steps.parse::<usize>().unwrap_or(0).min(Self::MAX_STEPS) {
+            0 => Vec::new(),
+            1 => vec![from],
+            n => (0..n).map(|i| from + (to - from) * i as f64 / (n - 1) as f64).collect(),
+        }
+    }
+}
+
+impl Default for SensitivityAxis {
+    fn default() -> Self {
+        Self { assumption: Assumption::Discount, from: "1.06".into(), to: "1.10".into(), steps: "5".into() }
+    }
+}
+
+/// The two axes of the sensitivity table.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensitivityInputs {
+    pub rows: SensitivityAxis,
+    pub columns: SensitivityAxis,
+}
+
+impl Default for SensitivityInputs {
+    fn default() -> Self {
+        Self {
+            rows: SensitivityAxis::default(),
+            columns: SensitivityAxis {
+                assumption: Assumption::Growth,
+                from: "1.00".into(),
+                to: "1.04".into(),
+                steps: "5".into(),
+            },
+        }
+    }
+}
+
+/// DCF results over every combination of two assumptions.
+#[derive(Clone, Serialize)]
+pub struct SensitivityTable {
+    pub rows: Assumption,
+    pub columns: Assumption,
+    pub row_values: Vec<f64>,
+    pub column_values: Vec<f64>,
+    /// `results[i][j]` at `row_values[i]` and `column_values[j]`; `None` for invalid assumptions.
+    pub results: Vec<Vec<Option<f64>>>,
+}
+
+impl SensitivityTable {
+    /// Smallest and largest valid result.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.results.iter().flatten().flatten().fold(None, |acc, &v| match acc {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+        })
+    }
+
+    /// Header row of the column values, then one line per row value; invalid cells are empty.
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("{} \\ {}", self.rows.label(), self.columns.label());
+        for v in &self.column_values {
+            out += &format!(",{v}");
+        }
+        out.push('\n');
+        for (v, results) in self.row_values.iter().zip(&self.results) {
+            out += &v.to_string();
+            for result in results {
+                out.push(',');
+                if let Some(result) = result {
+                    out += &result.to_string();
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl StateData {
+    /// [`Assumption::ALL`] followed by every usable parameter.
+    pub fn assumptions(&self) -> Vec<Assumption> {
+        let parameters = self.parameters.iter()
+            .zip(self.parameter_errors())
+            .filter(|(_, err)| err.is_none())
+            .map(|(p, _)| Assumption::Parameter(p.name.clone()));
+        Assumption::ALL.into_iter().chain(parameters).collect()
+    }
+
+    /// Current value of `assumption`; empty for a parameter that does not exist.
+    pub fn assumption(&self, assumption: &Assumption) -> &str {
+        match assumption {
+            Assumption::Discount => &self.discount,
+            Assumption::Growth => &self.growth,
+            Assumption::ExitMultiple => &self.terminal.exit_multiple,
+            Assumption::HModelGrowth => &self.terminal.h_model_growth,
+            Assumption::FadeGrowth => &self.terminal.fade_growth,
+            Assumption::OdeStepSize => &self.ode_step_size,
+            Assumption::Parameter(name) => self.parameters.iter()
+                .find(|p| &p.name == name)
+                .map(|p| p.value.as_str())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The field behind `assumption`, for flexing it; `None` for a parameter that does not exist.
+    ///
+    /// Flexing the discount rate turns the WACC builder off, which would otherwise override it.
+    pub fn assumption_mut(&mut self, assumption: &Assumption) -> Option<&mut String> {
+        match assumption {
+            Assumption::Discount => {
+                self.wacc.enabled = false;
+                Some(&mut self.discount)
+            },
+            Assumption::Growth => Some(&mut self.growth),
+            Assumption::ExitMultiple => Some(&mut self.terminal.exit_multiple),
+            Assumption::HModelGrowth => Some(&mut self.terminal.h_model_growth),
+            Assumption::FadeGrowth => Some(&mut self.terminal.fade_growth),
+            Assumption::OdeStepSize => Some(&mut self.ode_step_size),
+            // the first one of a name is the one the expressions see
+            Assumption::Parameter(name) => self.parameters.iter_mut()
+                .find(|p| &p.name == name)
+                .map(|p| &mut p.value),
+        }
+    }
+
+    /// Re-runs the whole valuation for every cell of the [`SensitivityInputs`] grid.
+    pub fn sensitivity_table(&self) -> SensitivityTable {
+        let SensitivityInputs { rows, columns } = &self.sensitivity;
+        let row_values = rows.values();
+        let column_values = columns.values();
+
+        let results = row_values.iter()
+            .map(|&r| {
+                let mut state = self.clone();
+                if let Some(value) = state.assumption_mut(&rows.assumption) {
+                    *value = r.to_string();
+                }
+                column_values.iter()
+                    .map(|&c| {
+                        if let Some(value) = state.assumption_mut(&columns.assumption) {
+                            *value = c.to_string();
+                        }
+                        state.evaluate().total
+                    })
+                    .collect()
+            })
+            .collect();
+
+        SensitivityTable {
+            rows: rows.assumption.clone(),
+            columns: columns.assumption.clone(),
+            row_values,
+            column_values,
+            results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row, Parameter};
+
+    fn state() -> StateData {
+        StateData {
+            rows: vec![row("1", "100")],
+            sensitivity: SensitivityInputs {
+                rows: SensitivityAxis { assumption: Assumption::Discount, from: "1.1".into(), to: "1.2".into(), steps: "3".into() },
+                columns: SensitivityAxis { assumption: Assumption::Growth, from: "1.0".into(), to: "1.1".into(), steps: "2".into() },
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn axis_values() {
+        let axis = |from: &str, to: &str, steps: &str| {
+            SensitivityAxis { assumption: Assumption::Growth, from: from.into(), to: to.into(), steps: steps.into() }.values()
+        };
+        assert_eq!(axis("1", "2", "5"), [1.0, 1.25, 1.5, 1.75, 2.0]);
+        assert_eq!(axis("1", "2", "1"), [1.0]);
+        assert!(axis("1", "2", "0").is_empty());
+        assert!(axis("x", "2", "5").is_empty());
+        assert_eq!(axis("0", "1", "1000").len(), SensitivityAxis::MAX_STEPS);
+    }
+
+    #[test]
+    fn sensitivity_table() {
+        let state = state();
+        let table = state.sensitivity_table();
+        assert_eq!(table.row_values.len(), 3);
+        assert_eq!(table.column_values.len(), 2);
+        for (i, &discount) in table.row_values.iter().enumerate() {
+            for (j, &growth) in table.column_values.iter().enumerate() {
+                let mut expected = state.clone();
+                expected.discount = discount.to_string();
+                expected.growth = growth.to_string();
+                match expected.evaluate().total {
+                    Some(total) => assert_close(table.results[i][j].unwrap(), total),
+                    None => assert_eq!(table.results[i][j], None),
+                }
+            }
+        }
+        // growth 1.1 at a discount of 1.1 has no perpetuity
+        assert_eq!(table.results[0][1], None);
+        assert_close(table.results[0][0].unwrap(), 100.0 + 100.0 / 1.1 + 100.0 / 0.1 / 1.1);
+    }
+
+    #[test]
+    fn flexing_the_discount_turns_the_wacc_off() {
+        let mut state = state();
+        state.wacc.enabled = true;
+        let table = state.sensitivity_table();
+        state.wacc.enabled = false;
+        state.discount = "1.1".into();
+        state.growth = "1".into();
+        assert_close(table.results[0][0].unwrap(), state.evaluate().total.unwrap());
+    }
+
+    #[test]
+    fn to_csv() {
+        let table = state().sensitivity_table();
+        let csv = table.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "Discount Rate \\ Growth,1,1.1");
+        let first: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(first[0], "1.1");
+        assert_eq!(first[1].parse::<f64>().unwrap(), table.results[0][0].unwrap());
+        // the invalid cell is left empty
+        assert_eq!(first[2], "");
+    }
+
+    #[test]
+    fn parameters_are_assumptions() {
+        let mut state = state();
+        state.rows = vec![row("1", "base")];
+        state.parameters = vec![
+            Parameter { name: "base".into(), value: "100".into(), ..Default::default() },
+            Parameter { name: "t".into(), value: "1".into(), ..Default::default() },
+        ];
+        let base = Assumption::Parameter("base".into());
+        assert_eq!(state.assumptions().last(), Some(&base));
+        assert_eq!(state.assumptions().len(), Assumption::ALL.len() + 1);
+        assert_eq!(state.assumption(&base), "100");
+        assert!(state.assumption_mut(&Assumption::Parameter("x".into())).is_none());
+
+        state.sensitivity.columns = SensitivityAxis { assumption: base, from: "100".into(), to: "200".into(), steps: "2".into() };
+        let table = state.sensitivity_table();
+        assert!(table.to_csv().starts_with("Discount Rate \\ Parameter base,100,200"));
+        // the row scales with the parameter
+        assert_close(table.results[0][1].unwrap(), 2.0 * table.results[0][0].unwrap());
+    }
+}
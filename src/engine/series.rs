@@ -0,0 +1,311 @@
+use std::fmt;
+use std::str::FromStr as _;
+
+use meval::{tokenizer::Token, Context, ContextProvider as _, Expr, FuncEvalError};
+use serde::{Deserialize, Serialize};
+
+use super::{parameters::is_identifier, Cashflow, Row, RowError, StateData, RESERVED_NAMES};
+
+/// Piecewise series with a name, referenced from any expression as `name(T)`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Series {
+    pub name: String,
+    pub rows: Vec<Row>,
+}
+
+/// Evaluated [`Series`], aligned with the cash-flow.
+#[derive(Clone, Default, Serialize)]
+pub struct NamedSeries {
+    pub name: String,
+    /// Empty when the series could not be evaluated, see [`Cashflow::series_errors`].
+    pub values: Vec<f64>,
+    /// One entry per row, like [`Cashflow::row_errors`].
+    pub row_errors: Vec<Option<RowError>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeriesError {
+    /// Not a letter or `_` followed by letters, digits or `_`.
+    InvalidName { name: String },
+    /// `t`, `y` or a built-in function such as `exp`.
+    ReservedName { name: String },
+    /// An earlier series or a parameter has the same name.
+    DuplicateName { name: String },
+    /// The series refer to each other in a loop; `names` follows the loop back to its start.
+    Cycle { names: Vec<String> },
+    /// The series chosen for discounting does not exist.
+    UnknownSeries { name: String },
+}
+
+impl fmt::Display for SeriesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SeriesError::InvalidName { name } if name.is_empty() => write!(f, "Series name is missing"),
+            SeriesError::InvalidName { name } => write!(f, "Invalid series name `{name}`"),
+            SeriesError::ReservedName { name } => write!(f, "`{name}` is a variable or function of the model"),
+            SeriesError::DuplicateName { name } => write!(f, "`{name}` is defined twice"),
+            SeriesError::Cycle { names } => write!(f, "Series refer to each other: {}", names.join(" → ")),
+            SeriesError::UnknownSeries { name } => write!(f, "There is no series `{name}` to discount"),
+        }
+    }
+}
+
+impl std::error::Error for SeriesError {}
+
+/// Value of `values` at period `x`, linear between periods and held flat outside them.
+fn value_at(values: &[f64], x: f64) -> f64 {
+    let Some(&last) = values.last() else {
+        return 0.0;
+    };
+    if x.is_nan() || x <= 0.0 {
+        return values[0];
+    }
+    let i = x.floor() as usize;
+    if i + 1 >= values.len() {
+        return last;
+    }
+    values[i] + (values[i + 1] - values[i]) * (x - i as f64)
+}
+
+impl StateData {
+    /// Diagnostic of each series name, `None` when the series can be referenced.
+    pub fn series_name_errors(&self) -> Vec<Option<SeriesError>> {
+        let builtins = Context::new();
+        self.series.iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let name = s.name.clone();
+                if !is_identifier(&name) {
+                    Some(SeriesError::InvalidName { name })
+                } else if RESERVED_NAMES.contains(&name.as_str())
+                    || !matches!(builtins.eval_func(&name, &[0.0]), Err(FuncEvalError::UnknownFunction))
+                {
+                    Some(SeriesError::ReservedName { name })
+                } else if self.series[..i].iter().any(|other| other.name == name)
+                    || self.parameters.iter().any(|p| p.name == name)
+                {
+                    Some(SeriesError::DuplicateName { name })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Indices of the series each series refers to through its expressions or discount rates.
+    fn series_dependencies(&self, valid: &[bool]) -> Vec<Vec<usize>> {
+        self.series.iter()
+            .map(|s| {
+                let mut deps: Vec<usize> = s.rows.iter()
+                    .flat_map(|row| [&row.expr, &row.discount])
+                    .filter_map(|expr| Expr::from_str(expr).ok())
+                    .flat_map(|parsed| parsed.iter()
+                        .filter_map(|token| match token {
+                            Token::Func(name, _) => self.series.iter()
+                                .enumerate()
+                                .position(|(j, other)| valid[j] && &other.name == name),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>())
+                    .collect();
+                deps.sort_unstable();
+                deps.dedup();
+                deps
+            })
+            .collect()
+    }
+
+    /// Evaluates the series in dependency order, each with the ones it refers to in scope.
+    ///
+    /// Series caught in a loop, and every series depending on one, are left unevaluated and
+    /// the loop is reported. Returns the series aligned with [`StateData::series`] next to the
+    /// full cash-flow of each evaluated one.
+    pub fn calculate_named_series(&self) -> (Vec<NamedSeries>, Vec<Option<Cashflow>>, Vec<SeriesError>) {
+        let name_errors = self.series_name_errors();
+        let valid: Vec<bool> = name_errors.iter().map(Option::is_none).collect();
+        let mut errors: Vec<SeriesError> = name_errors.into_iter().flatten().collect();
+        let deps = self.series_dependencies(&valid);
+
+        let mut output: Vec<NamedSeries> = self.series.iter()
+            .map(|s| NamedSeries { name: s.name.clone(), ..Default::default() })
+            .collect();
+        let mut cashflows: Vec<Option<Cashflow>> = vec![None; self.series.len()];
+        let mut done = vec![false; self.series.len()];
+        // in scope for the next series: the evaluated ones with a valid name
+        let mut scope: Vec<NamedSeries> = Vec::new();
+
+        let ready = |done: &[bool]| (0..done.len()).find(|&i| !done[i] && deps[i].iter().all(|&j| done[j]));
+        while let Some(i) = ready(&done) {
+            let cashflow = self.calculate_series(&self.series[i].rows, &scope);
+            output[i].values = cashflow.values.clone();
+            output[i].row_errors = cashflow.row_errors.clone();
+            if valid[i] {
+                scope.push(output[i].clone());
+            }
+            cashflows[i] = Some(cashflow);
+            done[i] = true;
+        }
+
+        // every series left waits on another one left, so following the waits must loop
+        if let Some(start) = (0..self.series.len()).find(|&i| !done[i]) {
+            let mut path = vec![start];
+            let mut next = start;
+            loop {
+                next = *deps[next].iter().find(|&&j| !done[j]).unwrap();
+                if let Some(k) = path.iter().position(|&i| i == next) {
+                    let mut names: Vec<String> = path[k..].iter().map(|&i| self.series[i].name.clone()).collect();
+                    names.push(self.series[next].name.clone());
+                    errors.push(SeriesError::Cycle { names });
+                    break;
+                }
+                path.push(next);
+            }
+        }
+
+        (output, cashflows, errors)
+    }
+
+    /// Built-in constants and functions, the [`parameters`](StateData::parameters) and the
+    /// evaluated `series` as functions of time.
+    ///
+    /// The argument of `name(x)` is an absolute period, so `name(T)` is the value of the series
+    /// at the row's own period whatever the [`TimeBase`](super::TimeBase).
+    pub(super) fn series_context(&self, series: &[NamedSeries]) -> Context<'static> {
+        let mut ctx = self.context();
+        for s in series {
+            let values = s.values.clone();
+            ctx.func(s.name.clone(), move |x| value_at(&values, x));
+        }
+        ctx
+    }
+}
+
+/// Rejects `expr` when it reads one of `series` as `name(t)` while `t` counts from period
+/// `start` rather than from 0, so that it is not the absolute period the series takes.
+/// Expressions that do not parse are left to the evaluation to report.
+pub(super) fn check_series_time(expr: &str, start: usize, series: &[NamedSeries]) -> Result<(), RowError> {
+    if start == 0 {
+        return Ok(());
+    }
+    let Ok(parsed) = Expr::from_str(expr) else {
+        return Ok(());
+    };
+    // in reverse Polish notation a lone `t` argument directly precedes the call
+    for pair in parsed.windows(2) {
+        if let [Token::Var(var), Token::Func(name, Some(1))] = pair {
+            if var == "t" && series.iter().any(|s| &s.name == name) {
+                return Err(RowError::LocalSeriesTime { name: name.clone(), start });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{tests::row, Parameter};
+
+    fn series(name: &str, expr: &str) -> Series {
+        Series { name: name.into(), rows: vec![row("2", expr)] }
+    }
+
+    #[test]
+    fn series_evaluate_in_dependency_order() {
+        let state = StateData {
+            series: vec![series("a", "b(t) * 2"), series("b", "t")],
+            ..Default::default()
+        };
+        let (output, _, errors) = state.calculate_named_series();
+        assert!(errors.is_empty());
+        assert_eq!(output[0].values, [0.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn cycle_is_reported_and_left_unevaluated() {
+        let state = StateData {
+            series: vec![series("a", "b(t)"), series("b", "a(t) + 1"), series("c", "5"), series("d", "a(t)")],
+            ..Default::default()
+        };
+        let (output, _, errors) = state.calculate_named_series();
+        assert_eq!(errors, [SeriesError::Cycle { names: vec!["a".into(), "b".into(), "a".into()] }]);
+        assert!(output[0].values.is_empty());
+        assert!(output[1].values.is_empty());
+        assert_eq!(output[2].values, [5.0, 5.0, 5.0]);
+        // depends on the loop without being part of it
+        assert!(output[3].values.is_empty());
+    }
+
+    #[test]
+    fn series_name_errors() {
+        let state = StateData {
+            series: vec![series("", "1"), series("exp", "1"), series("t", "1"), series("g", "1"), series("a", "1"), series("a", "1")],
+            parameters: vec![Parameter { name: "g".into(), value: "1".into(), ..Default::default() }],
+            ..Default::default()
+        };
+        assert_eq!(state.series_name_errors(), [
+            Some(SeriesError::InvalidName { name: "".into() }),
+            Some(SeriesError::ReservedName { name: "exp".into() }),
+            Some(SeriesError::ReservedName { name: "t".into() }),
+            Some(SeriesError::DuplicateName { name: "g".into() }),
+            None,
+            Some(SeriesError::DuplicateName { name: "a".into() }),
+        ]);
+    }
+
+    #[test]
+    fn discounted_series_replaces_the_rows() {
+        let mut state = StateData {
+            rows: vec![row("2", "costs(t)")],
+            series: vec![series("revenue", "100"), series("costs", "revenue(t) / 2")],
+            ..Default::default()
+        };
+        assert_eq!(state.calculate_cashflow().values, [50.0, 50.0, 50.0]);
+
+        state.discounted_series = "revenue".into();
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.values, [100.0, 100.0, 100.0]);
+        assert_eq!(cashflow.series[1].values, [50.0, 50.0, 50.0]);
+
+        state.discounted_series = "sales".into();
+        let cashflow = state.calculate_cashflow();
+        assert!(cashflow.values.is_empty());
+        assert_eq!(cashflow.series_errors, [SeriesError::UnknownSeries { name: "sales".into() }]);
+    }
+
+    #[test]
+    fn series_take_an_absolute_period() {
+        let mut state = StateData {
+            rows: vec![row("2", "revenue(t)"), row("4", "revenue(t + 2)")],
+            series: vec![Series { name: "revenue".into(), rows: vec![row("4", "10 * t")] }],
+            ..Default::default()
+        };
+        let cashflow = state.calculate_cashflow();
+        assert!(cashflow.row_errors.iter().all(Option::is_none));
+        assert_eq!(cashflow.values, [0.0, 10.0, 20.0, 30.0, 40.0]);
+
+        // `t` restarts at 2 in the second segment, so a bare `t` is rejected there
+        state.rows[1].expr = "revenue(t)".into();
+        state.rows[1].discount = "1 + revenue(t) / 100".into();
+        let cashflow = state.calculate_cashflow();
+        assert_eq!(cashflow.row_errors[1], Some(RowError::LocalSeriesTime { name: "revenue".into(), start: 2 }));
+        assert_eq!(cashflow.values[3..], [0.0, 0.0]);
+        assert_eq!(
+            check_series_time("1 + revenue(t) / 100", 2, &cashflow.series),
+            Err(RowError::LocalSeriesTime { name: "revenue".into(), start: 2 })
+        );
+        assert_eq!(check_series_time("revenue(t)", 0, &cashflow.series), Ok(()));
+        assert_eq!(check_series_time("exp(t)", 2, &cashflow.series), Ok(()));
+    }
+
+    #[test]
+    fn value_at_interpolates_and_holds() {
+        let values = [0.0, 10.0, 30.0];
+        assert_eq!(value_at(&values, -1.0), 0.0);
+        assert_eq!(value_at(&values, 1.5), 20.0);
+        assert_eq!(value_at(&values, 5.0), 30.0);
+        assert_eq!(value_at(&[], 1.0), 0.0);
+    }
+}
@@ -0,0 +1,348 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{DcfData, DiscountCurve, StateData, ValuationMode};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalMethod {
+    /// Gordon growth: `growth ^ t * y[last]` forever (or for `horizon` periods).
+    #[default]
+    PerpetualGrowth,
+    /// `exit_multiple * metric`, the metric being the last cash-flow unless given explicitly.
+    ExitMultiple,
+    /// Growth starts at `h_model_growth` and fades linearly to `growth`, `h_model_half_life`
+    /// being half of the fade period.
+    HModel,
+    /// Growth steps linearly from `fade_growth` to `growth` over `fade_periods`, followed by
+    /// a Gordon perpetuity.
+    Fade,
+}
+
+impl TerminalMethod {
+    pub const ALL: [TerminalMethod; 4] = [
+        TerminalMethod::PerpetualGrowth,
+        TerminalMethod::ExitMultiple,
+        TerminalMethod::HModel,
+        TerminalMethod::Fade,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TerminalMethod::PerpetualGrowth => "Perpetual Growth",
+            TerminalMethod::ExitMultiple => "Exit Multiple",
+            TerminalMethod::HModel => "H-Model",
+            TerminalMethod::Fade => "Finite Fade",
+        }
+    }
+}
+
+/// Longest fade the [`TerminalMethod::Fade`] method steps through.
+pub const MAX_FADE_PERIODS: u32 = 1000;
+
+/// Inputs of the terminal value methods other than the shared `growth`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalInputs {
+    pub method: TerminalMethod,
+    /// Number of periods perpetual growth is continued for; empty means forever.
+    pub horizon: String,
+    /// e.g. EV/EBITDA or EV/FCF
+    pub exit_multiple: String,
+    /// Metric the multiple applies to (e.g. terminal EBITDA); empty uses the last cash-flow.
+    pub exit_metric: String,
+    /// Initial (high) growth factor of the H-model.
+    pub h_model_growth: String,
+    /// Half of the period, in periods, over which the H-model growth fades.
+    pub h_model_half_life: String,
+    /// Growth factor at the start of the fade.
+    pub fade_growth: String,
+    /// Number of periods to fade over before reaching `growth`.
+    pub fade_periods: String,
+}
+
+impl Default for TerminalInputs {
+    fn default() -> Self {
+        Self {
+            method: TerminalMethod::default(),
+            horizon: "".into(),
+            exit_multiple: "10".into(),
+            exit_metric: "".into(),
+            h_model_growth: "1.08".into(),
+            h_model_half_life: "5".into(),
+            fade_growth: "1.08".into(),
+            fade_periods: "10".into(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct TerminalValue {
+    /// Value as of the last period.
+    pub value: f64,
+    /// Value discounted back to t = 0.
+    pub pv: f64,
+}
+
+/// Assumptions under which the terminal value has no finite, meaningful result.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TerminalValueError {
+    /// Perpetual growth (and the methods ending in it) only converges when `discount > growth`.
+    GrowthNotBelowDiscount { discount: f64, growth: f64 },
+    /// `horizon` is neither empty nor a whole number of periods.
+    InvalidHorizon,
+    /// `fade_periods` is not a whole number of at most [`MAX_FADE_PERIODS`].
+    InvalidFadePeriods,
+}
+
+impl fmt::Display for TerminalValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TerminalValueError::GrowthNotBelowDiscount { discount, growth } => write!(
+                f,
+                "Growth ({growth}) must be below the discount rate ({discount}) for a perpetual terminal value; \
+                 lower the growth, set a terminal horizon or use an exit multiple"
+            ),
+            TerminalValueError::InvalidHorizon => {
+                write!(f, "Terminal horizon must be empty or a whole number of periods")
+            },
+            TerminalValueError::InvalidFadePeriods => {
+                write!(f, "Fade periods must be a whole number of at most {MAX_FADE_PERIODS}")
+            },
+        }
+    }
+}
+
+impl std::error::Error for TerminalValueError {}
+
+impl StateData {
+    /// Terminal value of the selected [`TerminalMethod`].
+    pub fn terminal_value(&self, curve: &DiscountCurve, dcf_data: &[DcfData]) -> Result<TerminalValue, TerminalValueError> {
+        self.terminal_value_with(self.terminal.method, curve, dcf_data)
+    }
+
+    /// Terminal value as of the last period using `method`, plus its present value.
+    ///
+    /// Under mid- or beginning-of-period timing the growth methods are discounted from the time
+    /// the last cash-flow is discounted at, since their first cash-flow arrives one period later.
+    ///
+    /// The growth methods discount at the curve's [`tail_rate`](DiscountCurve::tail_rate), i.e.
+    /// the last segment's own rate when it has one, and at the global `discount` on a yield curve.
+    ///
+    /// Without a `horizon` perpetual growth is the Gordon growth perpetuity, which
+    /// requires `discount > growth`. With a horizon of `H` periods the growth is only continued
+    /// for `H` periods, which is finite for any growth.
+    pub fn terminal_value_with(
+        &self,
+        method: TerminalMethod,
+        curve: &DiscountCurve,
+        dcf_data: &[DcfData],
+    ) -> Result<TerminalValue, TerminalValueError> {
+        let Some(last) = dcf_data.last() else {
+            return Ok(TerminalValue { value: 0.0, pv: 0.0 });
+        };
+        let rate = curve.tail_rate().unwrap_or_else(|| self.discount.parse().unwrap_or(1.0));
+        let entered = (rate, self.growth.parse().unwrap_or(1.0));
+        let invalid = TerminalValueError::GrowthNotBelowDiscount { discount: entered.0, growth: entered.1 };
+        // the rates are per year
+        let (discount, growth): (f64, f64) = (self.per_period(entered.0), self.per_period(entered.1));
+        // value of a perpetuity whose first cash-flow is `next`
+        let perpetuity = |next: f64| {
+            if discount > growth {
+                Ok(next / (discount - growth))
+            } else {
+                Err(invalid)
+            }
+        };
+
+        let continuous = self.valuation_mode == ValuationMode::Continuous;
+
+        let value = match method {
+            // continuous flow growing like `growth ^ t`: ∫ y (growth / discount) ^ s ds
+            TerminalMethod::PerpetualGrowth if continuous => {
+                let decay = (discount / growth).ln();
+                match self.terminal.horizon.trim() {
+                    "" if decay > 0.0 => last.cashflow / decay,
+                    "" => return Err(invalid),
+                    horizon => {
+                        let horizon: u32 = horizon.parse().map_err(|_| TerminalValueError::InvalidHorizon)?;
+                        if decay == 0.0 {
+                            last.cashflow * f64::from(horizon)
+                        } else {
+                            last.cashflow * (1.0 - (growth / discount).powf(f64::from(horizon))) / decay
+                        }
+                    },
+                }
+            },
+            TerminalMethod::PerpetualGrowth => match self.terminal.horizon.trim() {
+                "" => perpetuity(last.cashflow * growth)?,
+                horizon => {
+                    let horizon: u32 = horizon.parse().map_err(|_| TerminalValueError::InvalidHorizon)?;
+                    finite_growth_sum(last.cashflow, growth / discount, horizon)
+                },
+            },
+            TerminalMethod::ExitMultiple => {
+                let multiple: f64 = self.terminal.exit_multiple.parse().unwrap_or(0.0);
+                let metric: f64 = self.terminal.exit_metric.parse().unwrap_or(last.cashflow);
+                multiple * metric
+            },
+            TerminalMethod::HModel => {
+                let high = self.terminal.h_model_growth.parse().map(|g| self.per_period(g)).unwrap_or(growth);
+                let half_life: f64 = self.terminal.h_model_half_life.parse().unwrap_or(0.0);
+                // (1 + gL) + H * (gS - gL), written with gross factors
+                perpetuity(last.cashflow * (growth + half_life * (high - growth)))?
+            },
+            TerminalMethod::Fade => {
+                let start = self.terminal.fade_growth.parse().map(|g| self.per_period(g)).unwrap_or(growth);
+                let periods: u32 = match self.terminal.fade_periods.trim() {
+                    "" => 0,
+                    periods => match periods.parse() {
+                        Ok(periods) if periods <= MAX_FADE_PERIODS => periods,
+                        _ => return Err(TerminalValueError::InvalidFadePeriods),
+                    },
+                };
+                let mut cashflow = last.cashflow;
+                let mut divisor = 1.0;
+                let mut value = 0.0;
+                for k in 1..=periods {
+                    cashflow *= start + (growth - start) * f64::from(k) / f64::from(periods);
+                    divisor *= discount;
+                    value += cashflow / divisor;
+                }
+                value + perpetuity(cashflow * growth)? / divisor
+            },
+        };
+        // An exit is a sale at the end of the last period. The growth methods value cash-flows
+        // that arrive like the explicit ones, so they follow the timing convention.
+        let last_period = dcf_data.len() - 1;
+        let pv = match method {
+            TerminalMethod::ExitMultiple => value / curve.factor(last_period as f64),
+            _ => value / curve.factor(self.discount_time(last_period)),
+        };
+
+        Ok(TerminalValue { value, pv })
+    }
+}
+
+/// `c * q + c * q^2 + ... + c * q^horizon` in closed form.
+fn finite_growth_sum(c: f64, q: f64, horizon: u32) -> f64 {
+    if q == 1.0 {
+        c * horizon as f64
+    } else {
+        c * q * (1.0 - q.powf(horizon as f64)) / (1.0 - q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{assert_close, tests::row, StateData, TerminalInputs, TerminalMethod, TerminalValueError, MAX_FADE_PERIODS};
+
+    fn state(growth: &str, horizon: &str) -> StateData {
+        StateData {
+            rows: vec![row("2", "100")],
+            growth: growth.into(),
+            discount: "1.1".into(),
+            terminal: TerminalInputs { horizon: horizon.into(), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn growth_not_below_discount() {
+        let valuation = state("1.1", "").evaluate();
+        assert_eq!(
+            valuation.terminal_value.unwrap_err(),
+            TerminalValueError::GrowthNotBelowDiscount { discount: 1.1, growth: 1.1 }
+        );
+        assert_eq!(valuation.total, None);
+    }
+
+    #[test]
+    fn horizon_matches_the_explicit_sum() {
+        let tv = state("1.2", "10").evaluate().terminal_value.unwrap();
+        let expected: f64 = (1..=10).map(|k| 100.0 * (1.2f64 / 1.1).powi(k)).sum();
+        assert_close(tv.value, expected);
+        assert_close(tv.pv, expected / 1.1f64.powi(2));
+    }
+
+    #[test]
+    fn horizon_with_growth_equal_to_discount() {
+        let tv = state("1.1", "7").evaluate().terminal_value.unwrap();
+        assert_close(tv.value, 700.0);
+    }
+
+    #[test]
+    fn invalid_horizon() {
+        for horizon in ["ten", "-1", "99999999999"] {
+            assert_eq!(state("1.0", horizon).evaluate().terminal_value.unwrap_err(), TerminalValueError::InvalidHorizon);
+        }
+    }
+
+    /// Last cash-flow of 100, discounted at 10% with a long-run growth of 3%.
+    fn method_state(method: TerminalMethod) -> StateData {
+        StateData {
+            rows: vec![row("1", "100")],
+            growth: "1.03".into(),
+            discount: "1.1".into(),
+            terminal: TerminalInputs {
+                method,
+                exit_multiple: "8".into(),
+                h_model_growth: "1.08".into(),
+                h_model_half_life: "5".into(),
+                fade_growth: "1.08".into(),
+                fade_periods: "2".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exit_multiple() {
+        let mut state = method_state(TerminalMethod::ExitMultiple);
+        assert_close(state.evaluate().terminal_value.unwrap().value, 800.0);
+        state.terminal.exit_metric = "50".into();
+        assert_close(state.evaluate().terminal_value.unwrap().value, 400.0);
+    }
+
+    #[test]
+    fn h_model() {
+        let tv = method_state(TerminalMethod::HModel).evaluate().terminal_value.unwrap();
+        // D0 * ((1 + gL) + H * (gS - gL)) / (r - gL)
+        assert_close(tv.value, 100.0 * (1.03 + 5.0 * 0.05) / 0.07);
+        assert_close(tv.pv, tv.value / 1.1);
+    }
+
+    #[test]
+    fn fade() {
+        let tv = method_state(TerminalMethod::Fade).evaluate().terminal_value.unwrap();
+        // growth steps from 8% to 3% over 2 periods: 5.5%, then 3%
+        let first = 100.0 * 1.055;
+        let second = first * 1.03;
+        let expected = first / 1.1 + second / 1.1f64.powi(2) + second * 1.03 / 0.07 / 1.1f64.powi(2);
+        assert_close(tv.value, expected);
+    }
+
+    #[test]
+    fn invalid_fade_periods() {
+        let mut state = method_state(TerminalMethod::Fade);
+        for periods in ["2.5", &(MAX_FADE_PERIODS + 1).to_string()] {
+            state.terminal.fade_periods = periods.into();
+            assert_eq!(state.evaluate().terminal_value.unwrap_err(), TerminalValueError::InvalidFadePeriods);
+        }
+        state.terminal.fade_periods = MAX_FADE_PERIODS.to_string();
+        assert!(state.evaluate().terminal_value.is_ok());
+    }
+
+    #[test]
+    fn h_model_growth_not_below_discount() {
+        let mut state = method_state(TerminalMethod::HModel);
+        state.growth = "1.1".into();
+        assert_eq!(
+            state.evaluate().terminal_value.unwrap_err(),
+            TerminalValueError::GrowthNotBelowDiscount { discount: 1.1, growth: 1.1 },
+        );
+    }
+}
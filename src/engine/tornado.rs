@@ -0,0 +1,308 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use super::{Assumption, Driver, Row, StateData, TerminalMethod};
+
+/// Rows an expression of the cash-flow belongs to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RowSource {
+    Rows,
+    Driver(Driver),
+    /// The [`Series`](super::Series) at this index.
+    Series(usize),
+}
+
+/// Input flexed by the tornado.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TornadoInput {
+    Assumption { assumption: Assumption },
+    /// Numeric literal at byte range `span` of the expression of row `row` of `source`.
+    Constant {
+        #[serde(skip)]
+        source: RowSource,
+        row: usize,
+        span: Range<usize>,
+        literal: String,
+    },
+}
+
+/// DCF results with one input flexed down and up, everything else at its base value.
+#[derive(Clone, Debug, Serialize)]
+pub struct TornadoBar {
+    pub input: TornadoInput,
+    pub label: String,
+    /// `None` when the flexed assumptions are invalid.
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+}
+
+impl TornadoBar {
+    /// Spread between the flexed results; invalid bars have none.
+    pub fn impact(&self) -> Option<f64> {
+        Some((self.high? - self.low?).abs())
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Tornado {
+    /// DCF result at the base values; `None` when the base itself is invalid.
+    pub base: Option<f64>,
+    /// Sorted by impact, largest first; invalid bars come last.
+    pub bars: Vec<TornadoBar>,
+}
+
+/// Byte ranges of the numeric literals in `expr`, leaving out digits that are part of a name.
+pub fn numeric_literals(expr: &str) -> Vec<Range<usize>> {
+    let bytes = expr.as_bytes();
+    let is_name = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_number = bytes[i].is_ascii_digit()
+            || (bytes[i] == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit));
+        if !starts_number || (i > 0 && is_name(bytes[i - 1])) {
+            // skip the rest of a name so `x12` is not split
+            if is_name(bytes[i]) {
+                while i < bytes.len() && is_name(bytes[i]) {
+                    i += 1;
+                }
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+            i += 1;
+        }
+        // exponent, e.g. `1e-3`
+        if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+            let mut j = i + 1;
+            if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+                j += 1;
+            }
+            if j < bytes.len() && bytes[j].is_ascii_digit() {
+                while j < bytes.len() && bytes[j].is_ascii_digit() {
+                    j += 1;
+                }
+                i = j;
+            }
+        }
+        out.push(start..i);
+    }
+    out
+}
+
+impl Assumption {
+    /// Gross factors (`1.08`) are flexed on their net rate (`0.08`), everything else on its value.
+    pub fn is_gross_factor(&self) -> bool {
+        matches!(
+            self,
+            Assumption::Discount | Assumption::Growth | Assumption::HModelGrowth | Assumption::FadeGrowth
+        )
+    }
+}
+
+impl StateData {
+    /// Flex as a fraction (`0.1` for 10%); invalid input means no flex.
+    pub fn tornado_flex(&self) -> f64 {
+        self.tornado_flex.parse::<f64>().map(|p| p / 100.0).unwrap_or(0.0)
+    }
+
+    /// Rows the cash-flow is evaluated from: every named series, plus the drivers or the rows
+    /// unless a series is discounted instead.
+    fn expression_rows(&self) -> Vec<(RowSource, &Vec<Row>)> {
+        let mut out = if !self.discounted_series.is_empty() {
+            Vec::new()
+        } else if self.drivers.enabled {
+            Driver::ALL.iter().map(|&d| (RowSource::Driver(d), self.drivers.rows(d))).collect()
+        } else {
+            vec![(RowSource::Rows, &self.rows)]
+        };
+        out.extend(self.series.iter().enumerate().map(|(i, s)| (RowSource::Series(i), &s.rows)));
+        out
+    }
+
+    /// Inputs that move the DCF result: discount, growth, the input of the selected terminal
+    /// method, ODE step size, the usable parameters and every non-zero literal in the expressions
+    /// of the rows, drivers and named series.
+    pub fn tornado_inputs(&self) -> Vec<TornadoInput> {
+        let terminal = match self.terminal.method {
+            TerminalMethod::PerpetualGrowth => None,
+            TerminalMethod::ExitMultiple => Some(Assumption::ExitMultiple),
+            TerminalMethod::HModel => Some(Assumption::HModelGrowth),
+            TerminalMethod::Fade => Some(Assumption::FadeGrowth),
+        };
+        let mut inputs: Vec<TornadoInput> = [Some(Assumption::Discount), Some(Assumption::Growth), terminal]
+            .into_iter()
+            .flatten()
+            .chain([Assumption::OdeStepSize])
+            .chain(self.assumptions().into_iter().filter(|a| matches!(a, Assumption::Parameter(_))))
+            .map(|assumption| TornadoInput::Assumption { assumption })
+            .collect();
+
+        for (source, rows) in self.expression_rows() {
+            for (row, r) in rows.iter().enumerate() {
+                for span in numeric_literals(&r.expr) {
+                    let literal = r.expr[span.clone()].to_string();
+                    if literal.parse::<f64>().is_ok_and(|v| v != 0.0) {
+                        inputs.push(TornadoInput::Constant { source, row, span, literal });
+                    }
+                }
+            }
+        }
+        inputs
+    }
+
+    pub fn tornado_label(&self, input: &TornadoInput) -> String {
+        match input {
+            TornadoInput::Assumption { assumption } => assumption.label(),
+            TornadoInput::Constant { source, row, literal, .. } => match source {
+                RowSource::Rows => format!("Row {}: {literal}", row + 1),
+                RowSource::Driver(driver) => format!("{} row {}: {literal}", driver.label(), row + 1),
+                RowSource::Series(i) => format!("Series {} row {}: {literal}", self.series[*i].name, row + 1),
+            },
+        }
+    }
+
+    /// Copy of the scenario with `input` multiplied by `factor`.
+    pub fn with_flexed(&self, input: &TornadoInput, factor: f64) -> StateData {
+        let mut state = self.clone();
+        match input {
+            TornadoInput::Assumption { assumption } => {
+                let value: f64 = self.assumption(assumption).parse().unwrap_or(0.0);
+                let flexed = if assumption.is_gross_factor() {
+                    1.0 + (value - 1.0) * factor
+                } else {
+                    value * factor
+                };
+                if let Some(value) = state.assumption_mut(assumption) {
+                    *value = flexed.to_string();
+                }
+            },
+            TornadoInput::Constant { source, row, span, literal } => {
+                let rows = match source {
+                    RowSource::Rows => &mut state.rows,
+                    RowSource::Driver(driver) => state.drivers.rows_mut(*driver),
+                    RowSource::Series(i) => &mut state.series[*i].rows,
+                };
+                let value: f64 = literal.parse().unwrap_or(0.0);
+                rows[*row].expr.replace_range(span.clone(), &(value * factor).to_string());
+            },
+        }
+        state
+    }
+
+    /// Flexes every [`tornado_inputs`](Self::tornado_inputs) down and up by
+    /// [`tornado_flex`](Self::tornado_flex), one at a time.
+    pub fn tornado(&self) -> Tornado {
+        let flex = self.tornado_flex();
+        let mut bars: Vec<TornadoBar> = self.tornado_inputs()
+            .into_iter()
+            .map(|input| TornadoBar {
+                label: self.tornado_label(&input),
+                low: self.with_flexed(&input, 1.0 - flex).evaluate().total,
+                high: self.with_flexed(&input, 1.0 + flex).evaluate().total,
+                input,
+            })
+            .collect();
+        bars.sort_by(|a, b| match (a.impact(), b.impact()) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+
+        Tornado { base: self.evaluate().total, bars }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row, Series};
+
+    fn literals(expr: &str) -> Vec<&str> {
+        numeric_literals(expr).into_iter().map(|span| &expr[span]).collect()
+    }
+
+    #[test]
+    fn numeric_literals_skip_names() {
+        assert_eq!(numeric_literals("100 * 1.1^t"), [0..3, 6..9]);
+        assert_eq!(literals("x12 + .5 - 2e-3 * exp(0.1 * t)"), [".5", "2e-3", "0.1"]);
+        // `e` without exponent digits is not part of the number
+        assert_eq!(literals("2e + 3E+2"), ["2", "3E+2"]);
+    }
+
+    #[test]
+    fn tornado_inputs_cover_the_terminal_method_and_non_zero_constants() {
+        let mut state = StateData { rows: vec![row("1", "100 + 0 * t"), row("3", "0.05 * y")], ..Default::default() };
+        state.terminal.method = TerminalMethod::ExitMultiple;
+        let labels: Vec<String> = state.tornado_inputs().iter().map(|i| state.tornado_label(i)).collect();
+        assert_eq!(labels.len(), 6);
+        assert_eq!(labels[0], Assumption::Discount.label());
+        assert_eq!(labels[2], Assumption::ExitMultiple.label());
+        assert_eq!(labels[4..], ["Row 1: 100", "Row 2: 0.05"]);
+    }
+
+    #[test]
+    fn tornado_inputs_flex_the_driver_rows_in_driver_mode() {
+        let mut state = StateData { rows: vec![row("1", "100")], ..Default::default() };
+        state.drivers.enabled = true;
+        state.drivers.rows_mut(Driver::Revenue).push(row("2", "500"));
+        let constants: Vec<TornadoInput> = state.tornado_inputs()
+            .into_iter()
+            .filter(|i| matches!(i, TornadoInput::Constant { .. }))
+            .collect();
+        assert_eq!(constants, [TornadoInput::Constant {
+            source: RowSource::Driver(Driver::Revenue),
+            row: 0,
+            span: 0..3,
+            literal: "500".into(),
+        }]);
+        let flexed = state.with_flexed(&constants[0], 1.1);
+        assert_eq!(flexed.drivers.rows(Driver::Revenue)[0].expr, "550");
+        assert_eq!(flexed.rows[0].expr, "100");
+    }
+
+    #[test]
+    fn tornado_inputs_flex_the_named_series() {
+        let mut state = StateData {
+            rows: vec![row("1", "100 + revenue(t)")],
+            series: vec![Series { name: "revenue".into(), rows: vec![row("1", "50")] }],
+            ..Default::default()
+        };
+        let labels: Vec<String> = state.tornado_inputs().iter().map(|i| state.tornado_label(i)).collect();
+        assert_eq!(labels[labels.len() - 2..], ["Row 1: 100", "Series revenue row 1: 50"]);
+        let input = state.tornado_inputs().pop().unwrap();
+        assert_eq!(state.with_flexed(&input, 2.0).series[0].rows[0].expr, "100");
+
+        // the rows are not valued while a series is
+        state.discounted_series = "revenue".into();
+        let labels: Vec<String> = state.tornado_inputs().iter().map(|i| state.tornado_label(i)).collect();
+        assert_eq!(labels.last().unwrap(), "Series revenue row 1: 50");
+        assert!(!labels.iter().any(|l| l.starts_with("Row ")));
+    }
+
+    #[test]
+    fn gross_factors_are_flexed_on_their_net_rate() {
+        let state = StateData::default();
+        let discount = TornadoInput::Assumption { assumption: Assumption::Discount };
+        assert_close(state.with_flexed(&discount, 1.1).discount.parse().unwrap(), 1.033);
+        let step = TornadoInput::Assumption { assumption: Assumption::OdeStepSize };
+        assert_close(state.with_flexed(&step, 0.9).ode_step_size.parse().unwrap(), 0.009);
+    }
+
+    #[test]
+    fn bars_are_sorted_by_impact() {
+        let state = StateData { rows: vec![row("3", "100")], ..Default::default() };
+        let tornado = state.tornado();
+        assert_close(tornado.base.unwrap(), state.evaluate().total.unwrap());
+        let impacts: Vec<f64> = tornado.bars.iter().map(|b| b.impact().unwrap()).collect();
+        assert!(impacts.windows(2).all(|w| w[0] >= w[1]), "{impacts:?}");
+        // the discount and growth rates drive the terminal value
+        assert_eq!(tornado.bars[0].label, Assumption::Discount.label());
+        assert_eq!(tornado.bars[1].label, Assumption::Growth.label());
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+use super::StateData;
+
+/// Inputs of the WACC builder. Rates are decimals (`0.04` for 4%).
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaccInputs {
+    /// Keep `StateData::discount` in sync with the built WACC.
+    pub enabled: bool,
+    pub risk_free_rate: String,
+    pub beta: String,
+    pub equity_risk_premium: String,
+    /// Pre-tax cost of debt
+    pub cost_of_debt: String,
+    pub tax_rate: String,
+    /// Equity and debt weights; any scale works (`60`/`40` or market values), they are normalized.
+    pub equity_weight: String,
+    pub debt_weight: String,
+}
+
+impl Default for WaccInputs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            risk_free_rate: "0.04".into(),
+            beta: "1.0".into(),
+            equity_risk_premium: "0.05".into(),
+            cost_of_debt: "0.05".into(),
+            tax_rate: "0.25".into(),
+            equity_weight: "0.7".into(),
+            debt_weight: "0.3".into(),
+        }
+    }
+}
+
+/// Breakdown of a built WACC.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct Wacc {
+    /// CAPM: `risk_free_rate + beta * equity_risk_premium`
+    pub cost_of_equity: f64,
+    pub after_tax_cost_of_debt: f64,
+    pub equity_weight: f64,
+    pub debt_weight: f64,
+    pub wacc: f64,
+}
+
+impl WaccInputs {
+    /// `None` when an input does not parse or the weights sum to zero.
+    pub fn build(&self) -> Option<Wacc> {
+        let risk_free_rate: f64 = self.risk_free_rate.parse().ok()?;
+        let beta: f64 = self.beta.parse().ok()?;
+        let equity_risk_premium: f64 = self.equity_risk_premium.parse().ok()?;
+        let cost_of_debt: f64 = self.cost_of_debt.parse().ok()?;
+        let tax_rate: f64 = self.tax_rate.parse().ok()?;
+        let equity: f64 = self.equity_weight.parse().ok()?;
+        let debt: f64 = self.debt_weight.parse().ok()?;
+        if equity + debt <= 0.0 {
+            return None;
+        }
+
+        let cost_of_equity = risk_free_rate + beta * equity_risk_premium;
+        let after_tax_cost_of_debt = cost_of_debt * (1.0 - tax_rate);
+        let equity_weight = equity / (equity + debt);
+        let debt_weight = debt / (equity + debt);
+        Some(Wacc {
+            cost_of_equity,
+            after_tax_cost_of_debt,
+            equity_weight,
+            debt_weight,
+            wacc: equity_weight * cost_of_equity + debt_weight * after_tax_cost_of_debt,
+        })
+    }
+}
+
+impl StateData {
+    /// The built WACC as a gross discount factor when the builder is enabled; `None` when it
+    /// is disabled or an input is invalid.
+    pub fn wacc_discount(&self) -> Option<String> {
+        if !self.wacc.enabled {
+            return None;
+        }
+        let discount = format!("{:.6}", 1.0 + self.wacc.build()?.wacc);
+        Some(discount.trim_end_matches('0').trim_end_matches('.').into())
+    }
+
+    /// Writes [`wacc_discount`](StateData::wacc_discount) into `discount`, so the field shows
+    /// the rate [`evaluate`](StateData::evaluate) uses.
+    ///
+    /// Returns `true` when `discount` was updated.
+    pub fn apply_wacc(&mut self) -> bool {
+        match self.wacc_discount() {
+            Some(discount) => {
+                self.discount = discount;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{assert_close, tests::row};
+
+    #[test]
+    fn build() {
+        let inputs = WaccInputs {
+            risk_free_rate: "0.04".into(),
+            beta: "1.2".into(),
+            equity_risk_premium: "0.05".into(),
+            cost_of_debt: "0.06".into(),
+            tax_rate: "0.25".into(),
+            equity_weight: "60".into(),
+            debt_weight: "40".into(),
+            ..Default::default()
+        };
+        let w = inputs.build().unwrap();
+        assert_close(w.cost_of_equity, 0.1);
+        assert_close(w.after_tax_cost_of_debt, 0.045);
+        assert_close(w.equity_weight, 0.6);
+        assert_close(w.debt_weight, 0.4);
+        assert_close(w.wacc, 0.6 * 0.1 + 0.4 * 0.045);
+    }
+
+    #[test]
+    fn build_accepts_negative_rates_and_beta() {
+        let inputs = WaccInputs { risk_free_rate: "-0.005".into(), beta: "-0.2".into(), ..Default::default() };
+        assert_close(inputs.build().unwrap().cost_of_equity, -0.005 - 0.2 * 0.05);
+    }
+
+    #[test]
+    fn build_rejects_invalid_inputs() {
+        assert!(WaccInputs { beta: "".into(), ..Default::default() }.build().is_none());
+        let zero = WaccInputs { equity_weight: "0".into(), debt_weight: "0".into(), ..Default::default() };
+        assert!(zero.build().is_none());
+    }
+
+    #[test]
+    fn evaluate_discounts_at_the_wacc() {
+        let mut state = StateData { rows: vec![row("1", "100")], ..Default::default() };
+        state.wacc.enabled = true;
+        // 0.7 * (0.04 + 0.05) + 0.3 * 0.05 * 0.75
+        assert_eq!(state.wacc_discount().as_deref(), Some("1.07425"));
+        assert_close(state.evaluate().dcf[1].dcf_unit, 100.0 / 1.07425);
+
+        state.wacc.enabled = false;
+        assert_eq!(state.wacc_discount(), None);
+        assert_close(state.evaluate().dcf[1].dcf_unit, 100.0 / 1.03);
+    }
+}
@@ -0,0 +1,248 @@
+//! Runs the `dcf_cli` binary on small scenario files.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const SCENARIO: &str = r#"{"rows":[{"end":"2","expr":"100"}],"growth":"1.0","discount":"1.1","ode_step_size":"0.01","use_log_scale":false}"#;
+
+/// Writes `json` to a scenario file unique to the test.
+fn scenario_file(name: &str, json: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("dcf_cli_{name}_{}.json", std::process::id()));
+    std::fs::write(&path, json).unwrap();
+    path
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dcf_cli")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn table() {
+    let path = scenario_file("table", SCENARIO);
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], format!("== {} ==", path.display()));
+    assert!(lines[1].contains("Cashflow") && lines[1].contains("Sum of DCF"));
+    // t = 0, 1, 2
+    assert!(lines[2..5].iter().all(|l| l.contains("100.000000")));
+    assert!(out.contains("DCF Result: "));
+}
+
+#[test]
+fn json() {
+    let path = scenario_file("json", SCENARIO);
+    let output = run(&["--format", "json", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let reports: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let report = &reports[0];
+    assert_eq!(report["file"], path.to_str().unwrap());
+    assert_eq!(report["dcf"].as_array().unwrap().len(), 3);
+    assert_eq!(report["dcf"][1]["cashflow"], 100.0);
+    assert!(report["total"].is_number());
+}
+
+#[test]
+fn csv() {
+    let path = scenario_file("csv", SCENARIO);
+    let output = run(&["-f", "csv", path.to_str().unwrap(), path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "file,t,date,cashflow,annualized_cashflow,dcf_unit,dcf_sum,terminal_value,terminal_value_pv,dcf_result,equity_value,per_share");
+    // one line per period of each file
+    assert_eq!(lines.len(), 1 + 2 * 3);
+    assert!(lines[1].starts_with(&format!("{},0,,100,100,100,100,", path.display())));
+}
+
+#[test]
+fn stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dcf_cli"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(SCENARIO.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("== - =="));
+}
+
+#[test]
+fn unknown_option() {
+    let output = run(&["--frmat", "csv"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8(output.stderr).unwrap();
+    assert!(err.contains("Unknown option: --frmat"));
+    assert!(err.contains("Usage: dcf_cli"));
+}
+
+#[test]
+fn row_error_fails() {
+    let path = scenario_file("row_error", &SCENARIO.replace("100", "100 * x"));
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let err = String::from_utf8(output.stderr.clone()).unwrap();
+    assert!(err.contains("row 1: Unknown variable `x`"));
+    // the report is still printed
+    assert!(stdout(&output).contains("DCF Result: "));
+}
+
+#[test]
+fn invalid_terminal_value_fails() {
+    let path = scenario_file("invalid_tv", &SCENARIO.replace(r#""growth":"1.0""#, r#""growth":"1.2""#));
+    let output = run(&["--format", "csv", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr.clone()).unwrap().contains("must be below the discount rate"));
+    // terminal value, its PV and the result are left empty
+    assert!(stdout(&output).lines().nth(1).unwrap().ends_with(",,,"));
+}
+
+#[test]
+fn wacc_is_the_discount_rate() {
+    let scenario = SCENARIO.replace(r#""use_log_scale":false"#, r#""use_log_scale":false,"wacc":{"enabled":true}"#);
+    let path = scenario_file("wacc", &scenario);
+    let output = run(&["--format", "csv", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    // 100 discounted once at the default WACC of 7.425%
+    let out = stdout(&output);
+    let row: Vec<&str> = out.lines().nth(2).unwrap().split(',').collect();
+    assert!((row[5].parse::<f64>().unwrap() - 100.0 / 1.07425).abs() < 1e-9);
+}
+
+#[test]
+fn scenarios_print_the_expected_result() {
+    let scenario = SCENARIO.replace(
+        r#""use_log_scale":false"#,
+        r#""use_log_scale":false,"scenarios":[
+            {"name":"Low","probability":"1","rows":[{"end":"2","expr":"100"}],"discount":"1.1","growth":"1.0"},
+            {"name":"High","probability":"3","rows":[{"end":"2","expr":"200"}],"discount":"1.1","growth":"1.0"}
+        ]"#,
+    );
+    let path = scenario_file("scenarios", &scenario);
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    assert!(out.contains("Scenario Low (25.0%): "));
+    assert!(out.contains("Scenario High (75.0%): "));
+    assert!(out.contains("Expected DCF Result: "));
+
+    let output = run(&["--format", "json", path.to_str().unwrap()]);
+    let reports: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let expected = &reports[0]["expected"];
+    let totals: Vec<f64> = (0..2).map(|i| expected["scenarios"][i]["total"].as_f64().unwrap()).collect();
+    let weighted = expected["expected"].as_f64().unwrap();
+    assert!((weighted - (0.25 * totals[0] + 0.75 * totals[1])).abs() < 1e-9);
+}
+
+#[test]
+fn implied_discount_rate() {
+    // 100 a period from t = 0 on, without growth, is worth 100 d / (d - 1): 1200 at d = 12 / 11
+    let scenario = SCENARIO.replace(r#""use_log_scale":false"#, r#""use_log_scale":false,"goal_seek":{"market_value":"1200"}"#);
+    let path = scenario_file("implied_discount", &scenario);
+    let output = run(&["--format", "json", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let reports: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let value = reports[0]["implied_discount"]["Ok"]["value"].as_f64().unwrap();
+    assert!((value - 12.0 / 11.0).abs() < 1e-9);
+
+    let output = run(&[scenario_file("no_target", SCENARIO).to_str().unwrap()]);
+    assert!(!stdout(&output).contains("Implied Discount Rate"));
+}
+
+#[test]
+fn implied_multiplier() {
+    // 100 a period is worth 1100 at d = 1.1 without growth, so 1650 needs 1.5 times the row
+    let scenario = SCENARIO.replace(
+        r#""use_log_scale":false"#,
+        r#""use_log_scale":false,"goal_seek":{"market_value":"1650","row":"1"}"#,
+    );
+    let path = scenario_file("implied_multiplier", &scenario);
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let line = out.lines().find(|l| l.starts_with("Implied Multiplier: ")).unwrap();
+    let value: f64 = line["Implied Multiplier: ".len()..].parse().unwrap();
+    assert!((value - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn calendar_dates() {
+    let scenario = SCENARIO.replace(
+        r#""use_log_scale":false"#,
+        r#""use_log_scale":false,"calendar":{"enabled":true,"valuation_date":"2024-12-31"}"#,
+    );
+    let path = scenario_file("calendar", &scenario);
+    let output = run(&["--format", "csv", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let dates: Vec<&str> = out.lines().skip(1).map(|l| l.split(',').nth(2).unwrap()).collect();
+    assert_eq!(dates, ["2024-12-31", "2025-12-31", "2026-12-31"]);
+
+    let output = run(&[path.to_str().unwrap()]);
+    let out = stdout(&output);
+    assert!(out.lines().nth(1).unwrap().trim_start().starts_with("Date"));
+    assert!(out.lines().nth(3).unwrap().trim_start().starts_with("2025-12-31"));
+}
+
+#[test]
+fn sub_annual_periods_are_annualized() {
+    let scenario = SCENARIO.replace(r#""use_log_scale":false"#, r#""use_log_scale":false,"frequency":"quarterly""#);
+    let path = scenario_file("quarterly", &scenario);
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let header = out.lines().nth(1).unwrap();
+    assert!(header.trim_start().starts_with("Quarter") && header.contains("Annualized"));
+    assert!(out.lines().nth(2).unwrap().contains("400.000000"));
+
+    let output = run(&["--format", "csv", path.to_str().unwrap()]);
+    let out = stdout(&output);
+    let row: Vec<&str> = out.lines().nth(2).unwrap().split(',').collect();
+    assert_eq!(row[3..5], ["100", "400"]);
+    // a quarter at 10% a year
+    assert!((row[5].parse::<f64>().unwrap() - 100.0 / 1.1f64.powf(0.25)).abs() < 1e-9);
+}
+
+#[test]
+fn parameters() {
+    let scenario = SCENARIO.replace(r#""expr":"100""#, r#""expr":"base""#).replace(
+        r#""use_log_scale":false"#,
+        r#""use_log_scale":false,"parameters":[{"name":"base","value":"100"},{"name":"t","value":"1"}]"#,
+    );
+    let path = scenario_file("parameters", &scenario);
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr.clone()).unwrap().contains("parameter 2: `t` is a variable of the model"));
+    assert!(stdout(&output).lines().nth(2).unwrap().contains("100.000000"));
+}
+
+#[test]
+fn named_series() {
+    let scenario = SCENARIO.replace(r#""expr":"100""#, r#""expr":"revenue(t) / 2""#).replace(
+        r#""use_log_scale":false"#,
+        r#""use_log_scale":false,"series":[{"name":"revenue","rows":[{"end":"2","expr":"200"}]}]"#,
+    );
+    let path = scenario_file("series", &scenario);
+    let output = run(&["--format", "csv", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(stdout(&output).lines().nth(1).unwrap().contains(",0,,100,"));
+
+    let path = scenario_file("series_loop", &scenario.replace(r#""expr":"200""#, r#""expr":"revenue(t)""#));
+    let output = run(&[path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Series refer to each other: revenue → revenue"));
+}
+
+#[test]
+fn missing_file() {
+    let output = run(&["does_not_exist.json"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("does_not_exist.json"));
+}